@@ -1,5 +1,11 @@
+use crate::ocr::{
+    plugin::PluginSpec,
+    preprocess::{Deskew, PipelineSpec},
+    stack::FrameStack,
+    BoxPick, Selection,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
 
@@ -22,6 +28,77 @@ pub struct RegionExpectation {
     pub total_digits: Option<u32>,
     /// Maximum allowed absolute change from the previous accepted value.
     pub max_deviation: Option<f64>,
+    /// Physical quantity shown by the region (e.g. "voltage", "temperature").
+    /// Readings whose printed unit measures a different quantity are rejected.
+    pub quantity: Option<String>,
+    /// Unit that readings are normalised to (e.g. "V", "mA", "°C").
+    /// `min`, `max` and `max_deviation` are expressed in this unit.
+    pub unit: Option<String>,
+    /// OCR character confusions repaired inside number tokens (e.g. `'B'` → `'8'`).
+    /// Absent → `ocr::DEFAULT_CONFUSIONS`; an empty map disables substitution.
+    pub confusions: Option<BTreeMap<char, char>>,
+    /// Restore a decimal point or minus sign the engines dropped, from the dot
+    /// and bar blobs between digit cells (see `ocr::read_region`).
+    /// Absent → on for numeric regions.
+    pub detect_punctuation: Option<bool>,
+    /// Whether the number may carry an exponent (`1.5e-3`), e.g. a composite
+    /// joined as `{m}e{e}`.  Off → an `E` after the digits is not part of it.
+    #[serde(default)]
+    pub exponent: bool,
+}
+
+/// Per-region OCR overrides.  Unset fields use the extraction-wide settings.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RegionSettings {
+    /// Engines to run, by recognizer name or family: `"oar-ocr"`, `"oar-ocr/gray"`,
+    /// `"tesseract"`, `"tesseract/channel-r"`, `"sevenseg"`, `"template"`, …
+    /// The seven-segment decoder and the trained template classifier only run
+    /// when selected here.
+    pub engines: Option<Vec<String>>,
+    /// Tesseract preprocessing variants to run (replaces the global preprocess
+    /// choice): preset names (`"binary"`, `"channel_r"`, …) or inline
+    /// pipelines (see `ocr::preprocess`).
+    pub preprocess: Option<Vec<PipelineSpec>>,
+    /// Pipelines to run oar-ocr on, one recognizer each, replacing its RGB
+    /// and grayscale variants.
+    pub oar_preprocess: Option<Vec<PipelineSpec>>,
+    /// Fast-path threshold for this region (see `ocr::read_region`).
+    pub fast_threshold: Option<f64>,
+    /// Tesseract languages for this region.
+    pub languages: Option<Vec<String>>,
+    /// Winner selection among engine candidates (`"best"` or `"rover"`).
+    pub selection: Option<Selection>,
+    /// How often to read this region; absent → every `fps_sample` frames.
+    pub sample_interval: Option<SampleInterval>,
+    /// Detect text inside the region and read only the box picked this way
+    /// (`"largest"`, `"center"` or `"expectation"`), so the region can be
+    /// drawn loosely around the display.
+    pub detect: Option<BoxPick>,
+    /// Undo rotation and italic shear of the crop before any engine reads it
+    /// (`{}` for the default search limits).
+    pub deskew: Option<Deskew>,
+    /// Combine the crop with the same region in neighbouring frames: suppress
+    /// moving glare, fill in multiplexed LED digits or super-resolve tiny
+    /// displays (see `ocr::stack`).
+    pub stack: Option<FrameStack>,
+}
+
+/// A sampling period, e.g. `{"seconds": 10}` or `{"frames": 1}`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleInterval {
+    Seconds(f64),
+    Frames(u32),
+}
+
+impl SampleInterval {
+    /// The interval in whole frames (at least 1) for a video at `fps`.
+    pub fn frames(self, fps: f64) -> u64 {
+        match self {
+            SampleInterval::Seconds(s) => (s * fps).round().max(1.0) as u64,
+            SampleInterval::Frames(n) => n.max(1) as u64,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,12 +110,92 @@ pub struct Region {
     pub height: i32,
 }
 
+/// Frame size in pixels.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Keyframe {
     pub timestamp: f64,
     pub regions: Vec<Region>,
 }
 
+/// A channel computed per frame from other regions' readings.
+/// See `derived.rs` for the formula language.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivedChannel {
+    pub name: String,
+    /// Formula over region names, e.g. `voltage * current` or `abs(t1 - t2)`.
+    pub expression: String,
+}
+
+/// A value assembled from several regions' text — e.g. mantissa and exponent,
+/// or integer and fractional digits, shown in separate display areas.
+///
+/// The children are ordinary regions in the keyframes; the composite's own
+/// expectation (keyed by `name` in `RegionConfig::expectations`) is applied
+/// to the joined text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeRegion {
+    pub name: String,
+    /// Join template, e.g. `{m}e{e}` or `{int}.{frac}`.
+    pub template: String,
+    /// Placeholder → child region name.  Placeholders not listed here refer
+    /// to the region of the same name.
+    #[serde(default)]
+    pub parts: HashMap<String, String>,
+    /// Also report the children's own readings (by default only the composite is).
+    #[serde(default)]
+    pub emit_children: bool,
+}
+
+impl CompositeRegion {
+    /// Child region names in template order (`{m}e{e}` → the regions bound to m, e).
+    pub fn children(&self) -> Result<Vec<&str>, String> {
+        let mut out = Vec::new();
+        let mut rest = self.template.as_str();
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..]
+                .find('}')
+                .ok_or_else(|| format!("Composite '{}': unclosed '{{' in template", self.name))?;
+            out.push(self.child_for(&rest[open + 1..open + close]));
+            rest = &rest[open + close + 1..];
+        }
+        if out.is_empty() {
+            return Err(format!(
+                "Composite '{}': template has no {{placeholders}}",
+                self.name
+            ));
+        }
+        Ok(out)
+    }
+
+    fn child_for<'a>(&'a self, placeholder: &'a str) -> &'a str {
+        self.parts
+            .get(placeholder)
+            .map(String::as_str)
+            .unwrap_or(placeholder)
+    }
+
+    /// Fill the template with each child's text.
+    /// Returns `None` if any child has no text for this frame.
+    pub fn join(&self, mut text_of: impl FnMut(&str) -> Option<String>) -> Option<String> {
+        let mut out = String::new();
+        let mut rest = self.template.as_str();
+        while let Some(open) = rest.find('{') {
+            let close = open + rest[open..].find('}')?;
+            out.push_str(&rest[..open]);
+            out.push_str(&text_of(self.child_for(&rest[open + 1..close]))?);
+            rest = &rest[close + 1..];
+        }
+        out.push_str(rest);
+        Some(out)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionConfig {
     pub video_path: String,
@@ -46,6 +203,50 @@ pub struct RegionConfig {
     /// Per-region-name content expectations.  Absent from old configs → empty map.
     #[serde(default)]
     pub expectations: HashMap<String, RegionExpectation>,
+    /// Values joined from several child regions' text.
+    #[serde(default)]
+    pub composites: Vec<CompositeRegion>,
+    /// Channels computed from other regions, evaluated in order after OCR.
+    #[serde(default)]
+    pub derived: Vec<DerivedChannel>,
+    /// Frame size the region coordinates were drawn on.  Absent from old
+    /// configs → coordinates are used as-is on any video.
+    #[serde(default)]
+    pub reference_resolution: Option<Resolution>,
+    /// Per-region-name OCR engine overrides.
+    #[serde(default)]
+    pub region_settings: HashMap<String, RegionSettings>,
+    /// External recognizer processes, run alongside the built-in engines.
+    #[serde(default)]
+    pub plugins: Vec<PluginSpec>,
+}
+
+/// Relative aspect-ratio difference tolerated before `scale_for` warns
+/// (absorbs rounding in sizes like 1920×1080 vs 1280×718).
+const ASPECT_TOLERANCE: f64 = 0.01;
+
+/// Per-axis scale and offset from reference coordinates to frame pixels.
+struct RegionScale {
+    sx: f64,
+    sy: f64,
+    dx: f64,
+    dy: f64,
+}
+
+impl RegionScale {
+    fn apply(&self, r: &Region) -> Region {
+        let x0 = (r.x as f64 * self.sx + self.dx).round();
+        let y0 = (r.y as f64 * self.sy + self.dy).round();
+        let x1 = ((r.x + r.width) as f64 * self.sx + self.dx).round();
+        let y1 = ((r.y + r.height) as f64 * self.sy + self.dy).round();
+        Region {
+            name: r.name.clone(),
+            x: x0 as i32,
+            y: y0 as i32,
+            width: (x1 - x0) as i32,
+            height: (y1 - y0) as i32,
+        }
+    }
 }
 
 impl RegionConfig {
@@ -60,13 +261,85 @@ impl RegionConfig {
         });
     }
 
-    /// Linearly interpolate region positions at the given timestamp.
+    /// Shift every keyframe's regions by `(dx, dy)` pixels (in reference
+    /// coordinates) — e.g. a shared layout on a camera that sat slightly off.
+    pub fn offset_regions(&mut self, dx: i32, dy: i32) {
+        for kf in &mut self.keyframes {
+            for r in &mut kf.regions {
+                r.x += dx;
+                r.y += dy;
+            }
+        }
+    }
+
+    /// How regions drawn at `reference_resolution` map onto a frame of `target`
+    /// size (e.g. a config drawn on the 4K master used on a 1080p proxy).
+    ///
+    /// Matching aspect ratios are scaled per axis.  Otherwise the video is
+    /// assumed to be letter-/pillar-boxed: regions are scaled uniformly by the
+    /// smaller factor, centred, and a warning describing the mismatch is returned.
+    /// `None` when no reference is recorded or the sizes already match.
+    fn scale_for(&self, target: Resolution) -> Option<(RegionScale, Option<String>)> {
+        let reference = self.reference_resolution?;
+        if reference == target || reference.width == 0 || reference.height == 0 {
+            return None;
+        }
+
+        let sx = target.width as f64 / reference.width as f64;
+        let sy = target.height as f64 / reference.height as f64;
+        let ref_aspect = reference.width as f64 / reference.height as f64;
+        let tgt_aspect = target.width as f64 / target.height.max(1) as f64;
+
+        if (ref_aspect / tgt_aspect - 1.0).abs() <= ASPECT_TOLERANCE {
+            let scale = RegionScale {
+                sx,
+                sy,
+                dx: 0.0,
+                dy: 0.0,
+            };
+            return Some((scale, None));
+        }
+        let s = sx.min(sy);
+        let scale = RegionScale {
+            sx: s,
+            sy: s,
+            dx: (target.width as f64 - reference.width as f64 * s) / 2.0,
+            dy: (target.height as f64 - reference.height as f64 * s) / 2.0,
+        };
+        let warning = format!(
+            "Regions were drawn on {}×{} but the video is {}×{} (different aspect ratio); \
+             scaled by {s:.3} and centred — check region placement.",
+            reference.width, reference.height, target.width, target.height
+        );
+        Some((scale, Some(warning)))
+    }
+
+    /// Warning for a video of `target` size whose aspect ratio differs from
+    /// the one the regions were drawn on (see `get_regions_at`).
+    pub fn resolution_warning(&self, target: Resolution) -> Option<String> {
+        self.scale_for(target)?.1
+    }
+
+    /// Region positions at the given timestamp on a frame of `frame` size.
     /// **Requires keyframes to be sorted** — call `sort_keyframes()` first.
     ///
+    /// Positions are interpolated between keyframes (see `regions_at`), then
+    /// mapped from `reference_resolution` onto the frame (see `scale_for`).
+    pub fn get_regions_at(&self, ts: f64, frame: Resolution) -> Vec<Region> {
+        let regions = self.regions_at(ts);
+        match self.scale_for(frame) {
+            Some((scale, _)) => regions.iter().map(|r| scale.apply(r)).collect(),
+            None => regions,
+        }
+    }
+
+    /// Linearly interpolate region positions at the given timestamp, in
+    /// reference coordinates.
+    ///
     /// Regions that exist in both surrounding keyframes are interpolated.
     /// Regions that exist only in the earlier keyframe keep that position.
     /// Regions that exist only in the later keyframe appear at their position.
-    pub fn get_regions_at(&self, ts: f64) -> Vec<Region> {
+    fn regions_at(&self, ts: f64) -> Vec<Region> {
         let kfs = &self.keyframes;
         if kfs.is_empty() {
             return vec![];
@@ -0,0 +1,506 @@
+use crate::config::DerivedChannel;
+use std::collections::{HashMap, VecDeque};
+
+// ── Derived channels ──────────────────────────────────────────────────────────
+//
+// A derived channel is a formula over other channels' readings, evaluated once
+// per sampled frame (e.g. `voltage * current`, `abs(t_in - t_out)`).
+//
+// Grammar (usual precedence, `^` is right-associative):
+//
+//   expr  := term (('+' | '-') term)*
+//   term  := unary (('*' | '/') unary)*
+//   unary := '-' unary | power
+//   power := atom ('^' unary)?
+//   atom  := number | name | name '(' args ')' | '(' expr ')'
+//
+// Names are region names (or earlier derived channels); names containing
+// spaces or symbols are written in backticks: `` `Probe 1` - `Probe 2` ``.
+// `t` is the frame timestamp in seconds unless a channel of that name exists.
+//
+// Functions: abs(x), sqrt(x), min(a, b, …), max(a, b, …),
+//            lag(name[, n]) — value of `name` n of its own samples ago (default 1),
+//            rate(name)     — change of `name` per second since its previous sample.
+
+/// Parsed formula.
+#[derive(Debug, Clone)]
+enum Expr {
+    Num(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Bin(char, Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+    Lag(String, usize),
+    Rate(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Func {
+    Abs,
+    Sqrt,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(f64),
+    Name(String),
+    Op(char),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '+' | '-' | '*' | '/' | '^' => {
+                tokens.push(Token::Op(c));
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '`' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '`')
+                    .ok_or("unterminated `name`")?;
+                tokens.push(Token::Name(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            _ if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // Exponent: 1e-3, 2.5E6
+                if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                    let mut j = i + 1;
+                    if j < chars.len() && matches!(chars[j], '+' | '-') {
+                        j += 1;
+                    }
+                    if j < chars.len() && chars[j].is_ascii_digit() {
+                        i = j;
+                        while i < chars.len() && chars[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
+                let text: String = chars[start..i].iter().collect();
+                let n = text
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid number '{text}'"))?;
+                tokens.push(Token::Num(n));
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Name(chars[start..i].iter().collect()));
+            }
+            _ => return Err(format!("unexpected character '{c}'")),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn expect(&mut self, want: Token) -> Result<(), String> {
+        match self.next() {
+            Some(t) if t == want => Ok(()),
+            Some(t) => Err(format!("expected {want:?}, found {t:?}")),
+            None => Err(format!("expected {want:?}, found end of formula")),
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        while let Some(&Token::Op(op @ ('+' | '-'))) = self.peek() {
+            self.pos += 1;
+            lhs = Expr::Bin(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        while let Some(&Token::Op(op @ ('*' | '/'))) = self.peek() {
+            self.pos += 1;
+            lhs = Expr::Bin(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Op('-')) {
+            self.pos += 1;
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.atom()?;
+        if self.peek() == Some(&Token::Op('^')) {
+            self.pos += 1;
+            return Ok(Expr::Bin('^', Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Expr::Num(n)),
+            Some(Token::LParen) => {
+                let e = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(e)
+            }
+            Some(Token::Name(name)) => {
+                if self.peek() != Some(&Token::LParen) {
+                    return Ok(Expr::Var(name));
+                }
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::RParen) {
+                    args.push(self.expr()?);
+                    while self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                        args.push(self.expr()?);
+                    }
+                }
+                self.expect(Token::RParen)?;
+                call(&name, args)
+            }
+            Some(t) => Err(format!("unexpected {t:?}")),
+            None => Err("unexpected end of formula".to_string()),
+        }
+    }
+}
+
+/// Build a function-call node, checking arity and argument forms.
+fn call(name: &str, mut args: Vec<Expr>) -> Result<Expr, String> {
+    let func = match name {
+        "abs" => Func::Abs,
+        "sqrt" => Func::Sqrt,
+        "min" => Func::Min,
+        "max" => Func::Max,
+        "lag" | "rate" => {
+            let n = match (name, args.len()) {
+                ("lag", 1) | ("rate", 1) => 1,
+                ("lag", 2) => match args.pop() {
+                    Some(Expr::Num(n)) if n >= 1.0 && n.fract() == 0.0 => n as usize,
+                    _ => return Err("lag(): sample count must be a positive integer".to_string()),
+                },
+                _ => return Err(format!("{name}(): wrong number of arguments")),
+            };
+            let Some(Expr::Var(channel)) = args.pop() else {
+                return Err(format!("{name}(): first argument must be a channel name"));
+            };
+            return Ok(if name == "lag" {
+                Expr::Lag(channel, n)
+            } else {
+                Expr::Rate(channel)
+            });
+        }
+        _ => return Err(format!("unknown function '{name}'")),
+    };
+    let arity_ok = match func {
+        Func::Abs | Func::Sqrt => args.len() == 1,
+        Func::Min | Func::Max => !args.is_empty(),
+    };
+    if !arity_ok {
+        return Err(format!("{name}(): wrong number of arguments"));
+    }
+    Ok(Expr::Call(func, args))
+}
+
+fn parse(src: &str) -> Result<Expr, String> {
+    let mut p = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+    };
+    let e = p.expr()?;
+    if let Some(t) = p.peek() {
+        return Err(format!("unexpected {t:?} after end of formula"));
+    }
+    Ok(e)
+}
+
+impl Expr {
+    /// Channels read at the current frame (lag/rate references excluded).
+    fn current_refs<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Var(v) => out.push(v),
+            Expr::Neg(e) => e.current_refs(out),
+            Expr::Bin(_, a, b) => {
+                a.current_refs(out);
+                b.current_refs(out);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|a| a.current_refs(out)),
+            Expr::Rate(v) => out.push(v),
+            Expr::Num(_) | Expr::Lag(..) => {}
+        }
+    }
+
+    /// Deepest history access in samples (0 = no history needed).
+    fn history_depth(&self) -> usize {
+        match self {
+            Expr::Lag(_, n) => *n,
+            Expr::Rate(_) => 1,
+            Expr::Neg(e) => e.history_depth(),
+            Expr::Bin(_, a, b) => a.history_depth().max(b.history_depth()),
+            Expr::Call(_, args) => args.iter().map(Expr::history_depth).max().unwrap_or(0),
+            Expr::Num(_) | Expr::Var(_) => 0,
+        }
+    }
+}
+
+// ── Evaluation ────────────────────────────────────────────────────────────────
+//
+// Each channel keeps its own sample history: a region's value enters it only
+// on frames where the region was actually read, stamped with that frame's
+// time.  Values held between a slow region's samples still feed plain
+// references, but `lag` counts the region's own samples and `rate` divides by
+// the time between them.
+
+/// A channel's latest reading and the time it was taken.
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub timestamp: f64,
+    pub value: f64,
+    pub confidence: f64,
+}
+
+/// Samples of one channel: the current one and those before it, newest first.
+#[derive(Default)]
+struct History {
+    current: Option<(f64, f64)>,
+    previous: VecDeque<(f64, f64)>,
+}
+
+impl History {
+    /// Record `(timestamp, value)` unless it is already the current sample.
+    fn push(&mut self, timestamp: f64, value: f64, depth: usize) {
+        if self.current.is_some_and(|(t, _)| t == timestamp) {
+            return;
+        }
+        if let Some(old) = self.current.replace((timestamp, value)) {
+            self.previous.push_front(old);
+            self.previous.truncate(depth);
+        }
+    }
+}
+
+struct Scope<'a> {
+    timestamp: f64,
+    values: &'a HashMap<String, f64>,
+    history: &'a HashMap<String, History>,
+}
+
+impl Scope<'_> {
+    /// Evaluate `e`; `None` when an input is missing or the result is not finite.
+    fn eval(&self, e: &Expr) -> Option<f64> {
+        let v = match e {
+            Expr::Num(n) => *n,
+            Expr::Var(name) => match self.values.get(name) {
+                Some(v) => *v,
+                None if name == "t" => self.timestamp,
+                None => return None,
+            },
+            Expr::Neg(e) => -self.eval(e)?,
+            Expr::Bin(op, a, b) => {
+                let (a, b) = (self.eval(a)?, self.eval(b)?);
+                match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    '/' => a / b,
+                    _ => a.powf(b),
+                }
+            }
+            Expr::Call(func, args) => {
+                let vals: Option<Vec<f64>> = args.iter().map(|a| self.eval(a)).collect();
+                let vals = vals?;
+                match func {
+                    Func::Abs => vals[0].abs(),
+                    Func::Sqrt => vals[0].sqrt(),
+                    Func::Min => vals.into_iter().fold(f64::INFINITY, f64::min),
+                    Func::Max => vals.into_iter().fold(f64::NEG_INFINITY, f64::max),
+                }
+            }
+            Expr::Lag(name, n) => {
+                let h = self.history.get(name)?;
+                // Without a value now (not yet evaluated, or its read failed)
+                // the stored current sample is already one sample ago.
+                if self.values.contains_key(name) {
+                    h.previous.get(n - 1)?.1
+                } else {
+                    h.current.iter().chain(&h.previous).nth(n - 1)?.1
+                }
+            }
+            Expr::Rate(name) => {
+                let v = *self.values.get(name)?;
+                let h = self.history.get(name)?;
+                let (t, _) = h.current?;
+                let &(prev_t, prev_v) = h.previous.front()?;
+                (v - prev_v) / (t - prev_t)
+            }
+        };
+        v.is_finite().then_some(v)
+    }
+}
+
+/// One derived channel's value for a frame.
+pub struct DerivedValue {
+    pub name: String,
+    pub expression: String,
+    /// `None` when an input was missing or the formula was undefined (e.g. ÷0).
+    pub value: Option<f64>,
+    /// Lowest confidence among the channels the formula read at this frame.
+    pub confidence: f64,
+}
+
+/// Compiled derived channels plus the per-channel sample history `lag`/`rate` need.
+pub struct DerivedChannels {
+    channels: Vec<(DerivedChannel, Expr)>,
+    history: HashMap<String, History>,
+    depth: usize,
+}
+
+impl DerivedChannels {
+    /// Parse every channel's formula; the error names the offending channel.
+    pub fn compile(defs: &[DerivedChannel]) -> Result<Self, String> {
+        let channels = defs
+            .iter()
+            .map(|d| {
+                parse(&d.expression)
+                    .map(|e| (d.clone(), e))
+                    .map_err(|e| format!("Derived channel '{}': {e}", d.name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let depth = channels
+            .iter()
+            .map(|(_, e)| e.history_depth())
+            .max()
+            .unwrap_or(0);
+        Ok(DerivedChannels {
+            channels,
+            history: HashMap::new(),
+            depth,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Evaluate all channels, in declaration order, for one frame.
+    ///
+    /// `readings` maps channel name → its latest sample for every region with
+    /// a number, whether read at this frame or held from an earlier one; only
+    /// samples not seen before enter the history.  Later channels may
+    /// reference earlier ones.
+    pub fn evaluate(
+        &mut self,
+        timestamp: f64,
+        readings: &HashMap<String, Sample>,
+    ) -> Vec<DerivedValue> {
+        let mut values: HashMap<String, f64> =
+            readings.iter().map(|(k, r)| (k.clone(), r.value)).collect();
+        let mut confidences: HashMap<String, f64> = readings
+            .iter()
+            .map(|(k, r)| (k.clone(), r.confidence))
+            .collect();
+        if self.depth > 0 {
+            for (name, r) in readings {
+                self.history.entry(name.clone()).or_default().push(
+                    r.timestamp,
+                    r.value,
+                    self.depth,
+                );
+            }
+        }
+
+        let mut out = Vec::with_capacity(self.channels.len());
+        for (def, expr) in &self.channels {
+            let value = Scope {
+                timestamp,
+                values: &values,
+                history: &self.history,
+            }
+            .eval(expr);
+
+            let mut refs = Vec::new();
+            expr.current_refs(&mut refs);
+            let confidence = match value {
+                Some(_) => refs
+                    .iter()
+                    .filter_map(|r| confidences.get(*r))
+                    .fold(1.0f64, |a, &b| a.min(b)),
+                None => 0.0,
+            };
+
+            if let Some(v) = value {
+                values.insert(def.name.clone(), v);
+                confidences.insert(def.name.clone(), confidence);
+                if self.depth > 0 {
+                    self.history
+                        .entry(def.name.clone())
+                        .or_default()
+                        .push(timestamp, v, self.depth);
+                }
+            }
+            out.push(DerivedValue {
+                name: def.name.clone(),
+                expression: def.expression.clone(),
+                value,
+                confidence,
+            });
+        }
+        out
+    }
+}
+
+/// Format a derived value with at most six decimals and no trailing zeros.
+pub fn format_value(v: f64) -> String {
+    let s = format!("{v:.6}");
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
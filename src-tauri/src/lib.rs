@@ -1,10 +1,20 @@
 mod config;
+mod derived;
 mod ocr;
 mod processor;
+mod project;
+mod suggest;
+mod tune;
 mod video;
 
 use config::{load_config, save_config};
 use processor::{cancel_extract, extract, save_csv, CancelFlag};
+use project::{
+    add_glyph_sample, calibrate_project, extract_project, glyph_accuracy, load_project,
+    retrain_glyphs, save_project,
+};
+use suggest::suggest_regions;
+use tune::tune_region;
 use video::{get_frame, get_video_info};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -21,6 +31,15 @@ pub fn run() {
             extract,
             cancel_extract,
             save_csv,
+            load_project,
+            save_project,
+            extract_project,
+            add_glyph_sample,
+            retrain_glyphs,
+            glyph_accuracy,
+            calibrate_project,
+            suggest_regions,
+            tune_region,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
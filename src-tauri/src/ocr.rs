@@ -1,9 +1,25 @@
+pub mod calibration;
 pub mod oar;
+pub mod plugin;
+pub mod preprocess;
+pub mod sevenseg;
+pub mod stack;
+pub mod template;
 pub mod tesseract;
+pub mod units;
 
 use crate::config::RegionExpectation;
-use image::{DynamicImage, ImageBuffer, Rgb, RgbImage};
+use base64::Engine;
+use image::{
+    codecs::png::PngEncoder, DynamicImage, GrayImage, ImageBuffer, ImageEncoder, Luma, Rgb,
+    RgbImage,
+};
+use oar::{OarPipeline, TextBox};
+use preprocess::Skew;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::OnceLock;
 
 // ── Public types ─────────────────────────────────────────────────────────────
 
@@ -14,6 +30,42 @@ pub struct OcrResult {
     pub confidence: f64,     // 0.0 – 1.0
     pub preview_b64: String, // base64 PNG of what the engine actually processed
     pub engine_name: String, // e.g. "tesseract/binary", "oar-ocr/rgb"
+    /// Each recognised character of `text`; empty when the engine only
+    /// reports an overall score.
+    pub symbols: Vec<Symbol>,
+}
+
+/// One recognised character.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Symbol {
+    pub text: String,
+    pub confidence: f64,
+    /// `[x, y, width, height]` in crop pixels, when the engine locates it.
+    pub bbox: Option<[u32; 4]>,
+}
+
+/// Outcome of `read_region` for one region.
+#[derive(Clone, Default)]
+pub struct RegionReading {
+    /// Cleaned number for numeric regions, otherwise the trimmed text.
+    pub value: String,
+    pub confidence: f64,
+    pub raw_text: String,
+    pub preview_b64: String,
+    pub engine_name: String,
+    /// Unit suffix as printed on the display (e.g. "mV"); empty when absent.
+    pub unit: String,
+    /// `value` converted into the expectation's target unit (numeric regions only).
+    pub normalized: Option<f64>,
+    /// Confusion-table substitutions applied to the number (e.g. "O→0").
+    pub substitutions: Vec<String>,
+    /// Per-character detail of `raw_text` from the winning engine.
+    pub symbols: Vec<Symbol>,
+    /// Decimal point / minus sign restored from the crop, as "1234→12.34".
+    pub punctuation: Option<String>,
+    /// Rotation and shear the crop was corrected by (`RegionSettings::deskew`);
+    /// `None` when it was read as cut.
+    pub skew: Option<Skew>,
 }
 
 /// Every OCR backend implements this.
@@ -23,62 +75,198 @@ pub struct OcrResult {
 pub trait Recognizer: Send + Sync {
     fn name(&self) -> &str;
     fn recognize(&self, crop: &DynamicImage) -> Option<OcrResult>;
+
+    /// Recognize several crops at once; one result per crop, in order.
+    /// Engines with a batched inference path override this; the default
+    /// recognizes each crop on its own.
+    fn recognize_batch(&self, crops: &[DynamicImage]) -> Vec<Option<OcrResult>> {
+        crops.par_iter().map(|c| self.recognize(c)).collect()
+    }
+}
+
+/// How `read_region` picks the final reading from all engine candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Selection {
+    /// The single candidate with the best confidence × validation score.
+    #[default]
+    Best,
+    /// Character-level voting across all candidates (see `fuse_candidates`).
+    Rover,
+}
+
+/// Which detected text box a region with text detection enabled reads.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoxPick {
+    /// The box with the largest area.
+    Largest,
+    /// The box whose centre is closest to the region's centre.
+    Center,
+    /// The box whose best priority-engine reading scores highest against the
+    /// region's expectation (confidence × validation).
+    Expectation,
+}
+
+/// Characters a reading of `exp` may contain, for engines that can restrict
+/// their output (Tesseract's whitelist, CTC masking in oar-ocr).
+/// `None` = unrestricted (non-numeric regions).
+pub fn allowed_chars(exp: &RegionExpectation) -> Option<String> {
+    if !exp.numeric {
+        return None;
+    }
+    let mut set: BTreeSet<char> = "0123456789.,".chars().collect();
+    if exp.min.map_or(true, |m| m < 0.0) {
+        set.insert('-');
+    }
+    // Printed unit suffixes of the expected quantity stay readable.
+    let target = exp.unit.as_deref().and_then(units::parse_unit);
+    if let Some(q) = exp
+        .quantity
+        .as_deref()
+        .or(target.as_ref().map(|u| u.unit.quantity))
+    {
+        set.extend(units::symbol_chars(q));
+    }
+    Some(set.into_iter().collect())
 }
 
 // ── Orchestration ────────────────────────────────────────────────────────────
 
-/// Run OCR on a frame region.
+/// Run every engine over a batch of crops (engines in parallel, each with one
+/// `recognize_batch` call) and return the successful results per crop.
+pub fn recognize_all(
+    engines: &[Box<dyn Recognizer>],
+    crops: &[DynamicImage],
+) -> Vec<Vec<OcrResult>> {
+    let per_engine: Vec<Vec<Option<OcrResult>>> = engines
+        .par_iter()
+        .map(|e| e.recognize_batch(crops))
+        .collect();
+    let mut per_crop: Vec<Vec<OcrResult>> = vec![Vec::new(); crops.len()];
+    for results in per_engine {
+        for (slot, r) in per_crop.iter_mut().zip(results) {
+            slot.extend(r);
+        }
+    }
+    per_crop
+}
+
+/// Narrow a loosely drawn region's crop to the detected text box chosen by
+/// `pick`, so only that box is recognized.  The crop is returned unchanged
+/// when nothing is detected.  With `BoxPick::Expectation` the `engines`'
+/// results on the chosen box are returned too, so it is not read twice;
+/// without engines to score boxes it picks the largest.
+pub fn focus_crop(
+    crop: DynamicImage,
+    pipeline: &OarPipeline,
+    pick: BoxPick,
+    engines: &[Box<dyn Recognizer>],
+    expectation: Option<&RegionExpectation>,
+    prev_value: Option<f64>,
+) -> (DynamicImage, Option<Vec<OcrResult>>) {
+    let boxes = match pipeline.detect(&crop.to_rgb8()) {
+        Some(boxes) if !boxes.is_empty() => boxes,
+        _ => return (crop, None),
+    };
+    let cut = |b: &TextBox| crop.crop_imm(b.x, b.y, b.width, b.height);
+    let pick = match pick {
+        BoxPick::Expectation if engines.is_empty() => BoxPick::Largest,
+        pick => pick,
+    };
+
+    let mut results = None;
+    let chosen = match pick {
+        BoxPick::Largest => boxes.iter().max_by_key(|b| b.width * b.height),
+        BoxPick::Center => {
+            let (cx, cy) = (crop.width() as f64 / 2.0, crop.height() as f64 / 2.0);
+            let dist = |b: &&TextBox| {
+                let bx = b.x as f64 + b.width as f64 / 2.0 - cx;
+                let by = b.y as f64 + b.height as f64 / 2.0 - cy;
+                bx * bx + by * by
+            };
+            boxes.iter().min_by(|a, b| {
+                dist(a)
+                    .partial_cmp(&dist(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+        }
+        BoxPick::Expectation => {
+            let box_crops: Vec<DynamicImage> = boxes.iter().map(cut).collect();
+            let score = |results: &Vec<OcrResult>| {
+                results
+                    .iter()
+                    .map(|r| {
+                        r.confidence
+                            * expectation
+                                .map(|e| validation_score(&r.text, e, prev_value))
+                                .unwrap_or(1.0)
+                    })
+                    .fold(0.0f64, f64::max)
+            };
+            let per_box = recognize_all(engines, &box_crops);
+            let best = (0..boxes.len()).max_by(|&a, &b| {
+                score(&per_box[a])
+                    .partial_cmp(&score(&per_box[b]))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            results = best.and_then(|i| per_box.into_iter().nth(i));
+            best.map(|i| &boxes[i])
+        }
+    };
+    match chosen {
+        Some(b) => {
+            eprintln!(
+                "[ocr] detected {} text box(es); reading {}×{} at ({}, {})",
+                boxes.len(),
+                b.width,
+                b.height,
+                b.x,
+                b.y
+            );
+            (cut(b), results)
+        }
+        None => (crop, None),
+    }
+}
+
+/// Pick the reading for one region crop.
 ///
-/// - `priority` engines run first (in parallel, e.g. oar-ocr).
+/// - `priority_results` are the priority engines' results for `crop` (see
+///   `recognize_all`; the caller batches them across regions).
 ///   If the best priority result's *validation-adjusted* confidence reaches
 ///   `fast_threshold`, the `fallback` engines are skipped entirely.
 ///   Validation uses `expectation` if provided; a result that violates range /
 ///   decimal / digit constraints has its effective confidence reduced, which
 ///   may prevent a confident-but-wrong reading from short-circuiting fallback.
 /// - Otherwise `fallback` engines also run and the best candidate across **all**
-///   engines (scored by confidence × validation) wins.
+///   engines (scored by confidence × validation) wins — or, with
+///   `Selection::Rover`, the candidates are fused character by character and
+///   the fused string is used when it satisfies the hard constraints.
+/// - Numeric candidates first get a dropped decimal point or minus sign
+///   restored from the blobs between their digit boxes (see
+///   `repair_punctuation`), so the repaired text is what gets scored.
 /// - `prev_value`: the accepted numeric reading from the previous frame for this
-///   region, used to score deviation-constrained expectations.
-///
-/// Returns `(value, confidence, raw_text, preview_b64, engine_name)`.
+///   region (in the target unit), used to score deviation-constrained expectations.
 pub fn read_region(
-    frame_bytes: &[u8],
-    frame_width: u32,
-    frame_height: u32,
-    x: u32,
-    y: u32,
-    w: u32,
-    h: u32,
-    priority: &[Box<dyn Recognizer>],
+    crop: &DynamicImage,
+    mut priority_results: Vec<OcrResult>,
     fallback: &[Box<dyn Recognizer>],
     fast_threshold: f64,
+    selection: Selection,
     expectation: Option<&RegionExpectation>,
     prev_value: Option<f64>,
-) -> (String, f64, String, String, String) {
+) -> RegionReading {
     // Prefer numeric results when the region is marked as numeric.
     let filter_numeric = expectation.map_or(false, |e| e.numeric);
 
-    let x2 = (x + w).min(frame_width);
-    let y2 = (y + h).min(frame_height);
-    if x2 <= x || y2 <= y {
-        return (
-            String::new(),
-            0.0,
-            String::new(),
-            String::new(),
-            String::new(),
-        );
-    }
-
-    let crop = build_crop(frame_bytes, frame_width, frame_height, x, y, x2 - x, y2 - y);
-    let crop_dyn = DynamicImage::ImageRgb8(crop);
+    // Punctuation repairs, keyed by engine name.
+    let punctuation = expectation.filter(|e| e.numeric && e.detect_punctuation.unwrap_or(true));
+    let blobs = punctuation.map(|_| ink_blobs(crop)).unwrap_or_default();
+    let mut repairs: HashMap<String, String> = HashMap::new();
+    repair_all(&mut priority_results, &blobs, punctuation, &mut repairs);
 
     // ── Step 1: priority engines (fast path) ─────────────────────────────────
-    let mut priority_results: Vec<OcrResult> = priority
-        .par_iter()
-        .filter_map(|e| e.recognize(&crop_dyn))
-        .collect();
-
     // Fast-path: skip fallback only when the best priority result is confident
     // enough AND satisfies hard constraints.  An out-of-range result must not
     // short-circuit the fallback engines — one of them might produce a valid value.
@@ -89,21 +277,23 @@ pub fn read_region(
             .map(|e| validation_score(&best.text, e, prev_value))
             .unwrap_or(1.0);
         let eff_conf = best.confidence * v_score;
-        let numeric_ok = !filter_numeric || clean_number(&best.text).parse::<f64>().is_ok();
+        let numeric_ok = !filter_numeric || parses_numeric(&best.text, expectation);
         if eff_conf >= fast_threshold && numeric_ok {
             eprintln!(
                 "[ocr] fast-path via {} (eff={:.3} ≥ {:.3}), skipping fallback",
                 best.engine_name, eff_conf, fast_threshold
             );
-            return make_result(best.clone(), filter_numeric);
+            let repair = repairs.get(&best.engine_name).cloned();
+            return make_result(best.clone(), expectation, repair);
         }
     }
 
     // ── Step 2: fallback engines ──────────────────────────────────────────────
-    let fallback_results: Vec<OcrResult> = fallback
+    let mut fallback_results: Vec<OcrResult> = fallback
         .par_iter()
-        .filter_map(|e| e.recognize(&crop_dyn))
+        .filter_map(|e| e.recognize(crop))
         .collect();
+    repair_all(&mut fallback_results, &blobs, punctuation, &mut repairs);
 
     // Debug log all candidates
     for r in priority_results.iter().chain(fallback_results.iter()) {
@@ -121,24 +311,288 @@ pub fn read_region(
     // (min/max range, max_deviation).  If no engine produced a valid reading,
     // return an empty value rather than reporting a known-bad result.
     priority_results.extend(fallback_results);
-    match best_result_constrained(&priority_results, filter_numeric, expectation, prev_value) {
-        Some(w) => make_result(w.clone(), filter_numeric),
+    let best = best_result_constrained(&priority_results, filter_numeric, expectation, prev_value);
+
+    if selection == Selection::Rover {
+        // The pivot is the best candidate, valid or not: fusion may repair a
+        // reading no single engine got right.
+        let pivot = best
+            .or_else(|| best_result(&priority_results, filter_numeric, expectation, prev_value));
+        if let Some((fused, pivot)) =
+            pivot.and_then(|p| Some((fuse_candidates(&priority_results, p)?, p)))
+        {
+            let valid = expectation.map_or(true, |e| {
+                passes_hard_constraints(&fused.text, e, prev_value)
+            });
+            eprintln!(
+                "[ocr] rover: {:?} conf={:.3} valid={valid}",
+                fused.text, fused.confidence
+            );
+            if valid {
+                // The fused symbols carry no boxes; report the pivot's repair.
+                let repair = repairs.get(&pivot.engine_name).cloned();
+                return make_result(fused, expectation, repair);
+            }
+        }
+    }
+
+    match best {
+        Some(w) => make_result(w.clone(), expectation, repairs.get(&w.engine_name).cloned()),
         None => {
             // No candidate passed hard constraints — report empty.
             eprintln!("[ocr] hard-filter: no candidate satisfied constraints → empty");
-            (
-                String::new(),
-                0.0,
-                String::new(),
-                String::new(),
-                String::new(),
-            )
+            RegionReading::default()
+        }
+    }
+}
+
+/// Apply a region's expectation to text assembled outside the engines, e.g. a
+/// composite region's joined child readings.  Hard-constraint violations yield
+/// an empty reading, exactly as in `read_region`.
+pub fn interpret_text(
+    text: &str,
+    confidence: f64,
+    engine_name: &str,
+    expectation: Option<&RegionExpectation>,
+    prev_value: Option<f64>,
+) -> RegionReading {
+    if let Some(exp) = expectation {
+        if !passes_hard_constraints(text, exp, prev_value) {
+            return RegionReading::default();
+        }
+    }
+    make_result(
+        OcrResult {
+            text: text.to_string(),
+            confidence,
+            preview_b64: String::new(),
+            engine_name: engine_name.to_string(),
+            symbols: Vec::new(),
+        },
+        expectation,
+        None,
+    )
+}
+
+// ── ROVER fusion ──────────────────────────────────────────────────────────────
+//
+// Recognizer Output Voting Error Reduction, at character level:
+//   1. Align every candidate to the pivot (the best single candidate) with a
+//      Levenshtein alignment.
+//   2. Each pivot position becomes a column; each gap between positions an
+//      insertion slot.  Every candidate votes in every column and slot — for
+//      the character aligned there, for a deletion, or for "no insertion".
+//   3. Votes are weighted by the candidate's per-character confidence when the
+//      engine reports one, otherwise by its overall confidence.
+//   4. The fused string is the winner of each slot and column in order.
+
+/// One candidate aligned to a pivot of `n` characters.
+struct Alignment {
+    /// Character (and vote weight) aligned to each pivot position; `None` = deleted.
+    columns: Vec<Option<(char, f64)>>,
+    /// Characters inserted before pivot position `i` (index `n` = after the end).
+    inserts: Vec<(String, f64)>,
+    /// Weight for the votes this candidate casts for deletions / no insertion.
+    weight: f64,
+}
+
+/// Fuse all candidates into one string by weighted character voting.
+/// The result's `symbols` hold each character's share of the vote.
+fn fuse_candidates(results: &[OcrResult], pivot: &OcrResult) -> Option<OcrResult> {
+    let pivot_chars: Vec<char> = pivot.text.trim().chars().collect();
+    let n = pivot_chars.len();
+    let aligned: Vec<Alignment> = results
+        .iter()
+        .filter(|r| !r.text.trim().is_empty() && r.confidence > 0.0)
+        .map(|r| align_to(&pivot_chars, r))
+        .collect();
+    if aligned.len() < 2 {
+        return None;
+    }
+
+    let mut text = String::new();
+    let mut symbols = Vec::new();
+    let mut agreement = Vec::new();
+    for i in 0..=n {
+        // Insertion slot before column i.
+        let mut votes: Vec<(&str, f64)> = Vec::new();
+        for a in &aligned {
+            let (s, w) = &a.inserts[i];
+            let w = if s.is_empty() { a.weight } else { *w };
+            add_vote(&mut votes, s.as_str(), w);
+        }
+        let (ins, share) = winner(&votes)?;
+        for c in ins.chars() {
+            text.push(c);
+            symbols.push(Symbol {
+                text: c.to_string(),
+                confidence: share,
+                bbox: None,
+            });
+        }
+        if !ins.is_empty() {
+            agreement.push(share);
+        }
+
+        if i == n {
+            break;
+        }
+
+        // Column i: a character or a deletion ("").
+        let mut buf: Vec<String> = Vec::with_capacity(aligned.len());
+        let mut weights: Vec<f64> = Vec::with_capacity(aligned.len());
+        for a in &aligned {
+            match a.columns[i] {
+                Some((c, w)) => {
+                    buf.push(c.to_string());
+                    weights.push(w);
+                }
+                None => {
+                    buf.push(String::new());
+                    weights.push(a.weight);
+                }
+            }
         }
+        let mut votes: Vec<(&str, f64)> = Vec::new();
+        for (s, &w) in buf.iter().zip(&weights) {
+            add_vote(&mut votes, s.as_str(), w);
+        }
+        let (col, share) = winner(&votes)?;
+        if !col.is_empty() {
+            text.push_str(col);
+            symbols.push(Symbol {
+                text: col.to_string(),
+                confidence: share,
+                bbox: None,
+            });
+        }
+        agreement.push(share);
+    }
+
+    if text.trim().is_empty() {
+        return None;
+    }
+    // Overall confidence: mean vote share × the mean engine confidence.
+    let mean_share = agreement.iter().sum::<f64>() / agreement.len().max(1) as f64;
+    let mean_conf = aligned.iter().map(|a| a.weight).sum::<f64>() / aligned.len() as f64;
+    Some(OcrResult {
+        text,
+        confidence: (mean_share * mean_conf).clamp(0.0, 1.0),
+        preview_b64: pivot.preview_b64.clone(),
+        engine_name: "rover".to_string(),
+        symbols,
+    })
+}
+
+fn add_vote<'a>(votes: &mut Vec<(&'a str, f64)>, s: &'a str, w: f64) {
+    match votes.iter_mut().find(|(v, _)| *v == s) {
+        Some((_, total)) => *total += w,
+        None => votes.push((s, w)),
+    }
+}
+
+/// The entry with the most weight and its share of the total.
+fn winner<'a>(votes: &[(&'a str, f64)]) -> Option<(&'a str, f64)> {
+    let total: f64 = votes.iter().map(|(_, w)| w).sum();
+    let &(s, w) = votes
+        .iter()
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))?;
+    Some((s, if total > 0.0 { w / total } else { 0.0 }))
+}
+
+/// Levenshtein-align `r` to the pivot characters.
+fn align_to(pivot: &[char], r: &OcrResult) -> Alignment {
+    let chars: Vec<char> = r.text.trim().chars().collect();
+    let per_char = r.symbols.len() == chars.len();
+    let weight_of = |j: usize| {
+        if per_char {
+            r.symbols[j].confidence
+        } else {
+            r.confidence
+        }
+    };
+    let (n, m) = (pivot.len(), chars.len());
+
+    // cost[i][j] = edit distance between pivot[..i] and chars[..j].
+    let mut cost = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in cost.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, c) in cost[0].iter_mut().enumerate() {
+        *c = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let sub = cost[i - 1][j - 1] + usize::from(pivot[i - 1] != chars[j - 1]);
+            cost[i][j] = sub.min(cost[i - 1][j] + 1).min(cost[i][j - 1] + 1);
+        }
+    }
+
+    // Trace back from the end, filling columns and insertion slots.
+    let mut columns = vec![None; n];
+    let mut inserts: Vec<(String, f64)> = vec![(String::new(), 0.0); n + 1];
+    let (mut i, mut j) = (n, m);
+    while i > 0 || j > 0 {
+        if i > 0
+            && j > 0
+            && cost[i][j] == cost[i - 1][j - 1] + usize::from(pivot[i - 1] != chars[j - 1])
+        {
+            columns[i - 1] = Some((chars[j - 1], weight_of(j - 1)));
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && cost[i][j] == cost[i - 1][j] + 1 {
+            i -= 1; // pivot char deleted in this candidate
+        } else {
+            let slot = &mut inserts[i];
+            slot.0.insert(0, chars[j - 1]);
+            slot.1 = slot.1.max(weight_of(j - 1));
+            j -= 1;
+        }
+    }
+
+    Alignment {
+        columns,
+        inserts,
+        weight: r.confidence,
     }
 }
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
+/// Crop a region from an RGB frame, clipped to the frame.
+/// `None` when nothing of the region lies inside the frame.
+pub fn crop_region(
+    frame_bytes: &[u8],
+    frame_width: u32,
+    frame_height: u32,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+) -> Option<DynamicImage> {
+    let x2 = (x + w).min(frame_width);
+    let y2 = (y + h).min(frame_height);
+    if x2 <= x || y2 <= y {
+        return None;
+    }
+    let crop = build_crop(frame_bytes, frame_width, frame_height, x, y, x2 - x, y2 - y);
+    Some(DynamicImage::ImageRgb8(crop))
+}
+
+/// Whether an engine's `text` equals the ground truth `truth` once both are
+/// interpreted under `expectation`: numbers compare by (unit-normalised)
+/// value, anything else as text ignoring whitespace.
+pub fn matches_truth(text: &str, truth: &str, expectation: Option<&RegionExpectation>) -> bool {
+    if let Some(exp) = expectation.filter(|e| e.numeric) {
+        return match (interpret_numeric(text, exp), interpret_numeric(truth, exp)) {
+            (Some(a), Some(b)) => (a.value - b.value).abs() <= 1e-9 * b.value.abs().max(1.0),
+            _ => false,
+        };
+    }
+    let squash = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+    squash(text) == squash(truth)
+}
+
 fn build_crop(frame_bytes: &[u8], fw: u32, fh: u32, x: u32, y: u32, w: u32, h: u32) -> RgbImage {
     let img: ImageBuffer<Rgb<u8>, Vec<u8>> = ImageBuffer::from_fn(fw, fh, |px, py| {
         let off = ((py * fw + px) * 3) as usize;
@@ -147,6 +601,18 @@ fn build_crop(frame_bytes: &[u8], fw: u32, fh: u32, x: u32, y: u32, w: u32, h: u
     image::imageops::crop_imm(&img, x, y, w, h).to_image()
 }
 
+pub(crate) fn encode_png(bytes: &[u8], w: u32, h: u32, color: image::ExtendedColorType) -> String {
+    let mut png = Vec::new();
+    if PngEncoder::new(&mut png)
+        .write_image(bytes, w, h, color)
+        .is_ok()
+    {
+        base64::engine::general_purpose::STANDARD.encode(&png)
+    } else {
+        String::new()
+    }
+}
+
 /// Returns `true` when `text` satisfies the hard constraints in `exp`
 /// (min/max range, max_deviation from prev_value).
 /// Non-numeric results always fail when `exp.numeric` is set.
@@ -154,9 +620,8 @@ fn passes_hard_constraints(text: &str, exp: &RegionExpectation, prev_value: Opti
     if !exp.numeric {
         return true; // no numeric constraint → anything passes
     }
-    let cleaned = clean_number(text);
-    let Ok(v) = cleaned.parse::<f64>() else {
-        return false; // can't parse → violates numeric constraint
+    let Some(v) = interpret_numeric(text, exp).map(|n| n.value) else {
+        return false; // can't parse (or wrong unit) → violates numeric constraint
     };
     let in_range = exp.min.map_or(true, |m| v >= m) && exp.max.map_or(true, |m| v <= m);
     let ok_deviation = match (exp.max_deviation, prev_value) {
@@ -204,7 +669,7 @@ fn best_result_constrained<'a>(
         let numeric_indices: Vec<usize> = valid_indices
             .iter()
             .copied()
-            .filter(|&i| clean_number(&results[i].text).parse::<f64>().is_ok())
+            .filter(|&i| interpret_numeric(&results[i].text, exp).is_some())
             .collect();
         let pool = if numeric_indices.is_empty() {
             &valid_indices
@@ -258,7 +723,7 @@ fn best_result<'a>(
     if filter_numeric {
         let numeric: Vec<&OcrResult> = results
             .iter()
-            .filter(|r| clean_number(&r.text).parse::<f64>().is_ok())
+            .filter(|r| parses_numeric(&r.text, expectation))
             .collect();
         if !numeric.is_empty() {
             return numeric.into_iter().max_by(|a, b| {
@@ -286,9 +751,8 @@ fn validation_score(text: &str, exp: &RegionExpectation, prev_value: Option<f64>
         return 1.0; // no numeric expectation → no penalty
     }
 
-    let cleaned = clean_number(text);
-    let Ok(value) = cleaned.parse::<f64>() else {
-        return 0.1; // can't parse as number at all → heavy penalty
+    let Some(NumericReading { cleaned, value, .. }) = interpret_numeric(text, exp) else {
+        return 0.1; // can't parse as number (or wrong unit) → heavy penalty
     };
 
     let mut score = 1.0f64;
@@ -330,58 +794,209 @@ fn validation_score(text: &str, exp: &RegionExpectation, prev_value: Option<f64>
 }
 
 /// Number of digits after the decimal point in a numeric string ("3.14" → 2, "42" → 0).
+/// An exponent is ignored ("1.25e-3" → 2).
 fn count_decimal_places(s: &str) -> u32 {
+    let s = mantissa(s);
     s.find('.')
         .map(|pos| (s.len() - pos - 1) as u32)
         .unwrap_or(0)
 }
 
 /// Total count of ASCII digit characters in a string ("3.14" → 3, "-007" → 3).
+/// An exponent is ignored ("1.25e-3" → 3).
 fn count_total_digits(s: &str) -> u32 {
-    s.chars().filter(|c| c.is_ascii_digit()).count() as u32
+    mantissa(s).chars().filter(|c| c.is_ascii_digit()).count() as u32
+}
+
+/// The part of a cleaned number before any exponent ("1.25e-3" → "1.25").
+fn mantissa(s: &str) -> &str {
+    s.split(['e', 'E']).next().unwrap_or(s)
 }
 
-fn make_result(r: OcrResult, filter_numeric: bool) -> (String, f64, String, String, String) {
-    let value = if filter_numeric {
-        clean_number(&r.text)
+/// Length of an exponent suffix (`e-3`, `E+06`) at the start of `b`, or 0.
+fn exponent_len(b: &[u8]) -> usize {
+    if !matches!(b.first(), Some(b'e' | b'E')) {
+        return 0;
+    }
+    let sign = usize::from(matches!(b.get(1), Some(b'+' | b'-')));
+    let digits = b[1 + sign..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count();
+    if digits == 0 {
+        0
     } else {
-        r.text.trim().to_string()
+        1 + sign + digits
+    }
+}
+
+fn make_result(
+    r: OcrResult,
+    expectation: Option<&RegionExpectation>,
+    punctuation: Option<String>,
+) -> RegionReading {
+    let numeric = expectation.filter(|e| e.numeric);
+    let (value, unit, normalized, substitutions) = match numeric {
+        Some(e) => match interpret_numeric(&r.text, e) {
+            Some(n) => (n.cleaned, n.unit, Some(n.value), n.substitutions),
+            None => {
+                let (cleaned, subs) = clean_number_with(&r.text, confusions(e), e.exponent);
+                (cleaned, String::new(), None, subs)
+            }
+        },
+        None => (r.text.trim().to_string(), String::new(), None, Vec::new()),
     };
-    (
+    RegionReading {
         value,
-        r.confidence,
-        r.text.trim().to_string(),
-        r.preview_b64,
-        r.engine_name,
-    )
+        confidence: r.confidence,
+        raw_text: r.text.trim().to_string(),
+        preview_b64: r.preview_b64,
+        engine_name: r.engine_name,
+        unit,
+        normalized,
+        substitutions,
+        symbols: r.symbols,
+        punctuation,
+        // Set by the caller, which prepared the crop.
+        skew: None,
+    }
 }
 
-/// Normalise raw OCR output to a clean number string.
+// ── Numeric interpretation ────────────────────────────────────────────────────
+
+/// A candidate text interpreted as a number under a region's expectation.
+struct NumericReading {
+    /// Number token after OCR clean-up, as printed ("12.50").
+    cleaned: String,
+    /// Recognised unit suffix as printed ("mV"); empty when absent or unknown.
+    unit: String,
+    /// Value converted into the expectation's target unit.
+    value: f64,
+    /// Confusion-table substitutions applied while cleaning (e.g. "O→0").
+    substitutions: Vec<String>,
+}
+
+/// Interpret `text` as a number in the unit declared by `exp`.
+///
+/// A recognised unit suffix is converted into `exp.unit`; a missing or
+/// unrecognised suffix is assumed to already be in the target unit.  Returns
+/// `None` when no number can be parsed, or when the printed unit measures a
+/// different quantity than `exp.quantity` / `exp.unit` declare.
+fn interpret_numeric(text: &str, exp: &RegionExpectation) -> Option<NumericReading> {
+    let table = confusions(exp);
+    let (cleaned, substitutions) = clean_number_with(text, table, exp.exponent);
+    let raw: f64 = cleaned.parse().ok()?;
+
+    let suffix = units::unit_suffix(text, |c| table.contains_key(&c), exp.exponent);
+    let Some(printed) = units::parse_unit(&suffix) else {
+        return Some(NumericReading {
+            cleaned,
+            unit: String::new(),
+            value: raw,
+            substitutions,
+        });
+    };
+
+    if let Some(q) = exp.quantity.as_deref() {
+        if !printed.unit.quantity.eq_ignore_ascii_case(q.trim()) {
+            return None;
+        }
+    }
+    let value = match exp.unit.as_deref().and_then(units::parse_unit) {
+        Some(target) => units::convert(raw, &printed, &target)?,
+        None => raw,
+    };
+    Some(NumericReading {
+        cleaned,
+        unit: suffix,
+        value,
+        substitutions,
+    })
+}
+
+/// Whether `text` yields a usable number — unit-aware when an expectation is given.
+fn parses_numeric(text: &str, expectation: Option<&RegionExpectation>) -> bool {
+    match expectation {
+        Some(e) => interpret_numeric(text, e).is_some(),
+        None => clean_number(text).parse::<f64>().is_ok(),
+    }
+}
+
+/// Letter/digit confusions repaired when a region does not declare its own table.
+pub const DEFAULT_CONFUSIONS: &[(char, char)] = &[('O', '0'), ('l', '1'), ('I', '1'), ('S', '5')];
+
+fn default_confusions() -> &'static BTreeMap<char, char> {
+    static TABLE: OnceLock<BTreeMap<char, char>> = OnceLock::new();
+    TABLE.get_or_init(|| DEFAULT_CONFUSIONS.iter().copied().collect())
+}
+
+/// The confusion table for a region: its own map if set, else the default.
+fn confusions(exp: &RegionExpectation) -> &BTreeMap<char, char> {
+    exp.confusions
+        .as_ref()
+        .unwrap_or_else(|| default_confusions())
+}
+
+/// Normalise raw OCR output to a clean number string using the default confusion table.
 pub fn clean_number(text: &str) -> String {
+    clean_number_with(text, default_confusions(), false).0
+}
+
+/// Normalise raw OCR output to a clean number string.
+///
+/// Confusions from `table` are only repaired inside number tokens — runs of
+/// digits, `.`, `-` and confusable letters that contain at least one real
+/// digit — so unit suffixes and labels next to the number are left alone.
+/// With `exponent`, a trailing `e-3` / `E+06` is kept as part of the number.
+/// Returns the cleaned string and the substitutions that fired (e.g. `"O→0"`).
+pub fn clean_number_with(
+    text: &str,
+    table: &BTreeMap<char, char>,
+    exponent: bool,
+) -> (String, Vec<String>) {
     let line = text.lines().next().unwrap_or("").trim();
 
     // comma → decimal point; strip apostrophe thousands-separators
-    let s: String = line
+    let mut chars: Vec<char> = line
         .chars()
         .map(|c| if c == ',' { '.' } else { c })
         .filter(|c| *c != '\'')
         .collect();
 
-    // Common OCR letter/digit confusions
-    let s: String = s
-        .chars()
-        .map(|c| match c {
-            'O' => '0',
-            'l' | 'I' => '1',
-            'S' => '5',
-            _ => c,
-        })
-        .collect();
+    // Letter/digit confusions, applied per number token
+    let mut fired: Vec<String> = Vec::new();
+    let in_token = |c: char| c.is_ascii_digit() || c == '.' || c == '-' || table.contains_key(&c);
+    let mut i = 0;
+    while i < chars.len() {
+        if !in_token(chars[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && in_token(chars[i]) {
+            i += 1;
+        }
+        let token = &mut chars[start..i];
+        if !token.iter().any(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        for c in token.iter_mut() {
+            if let Some(&to) = table.get(c) {
+                let sub = format!("{c}→{to}");
+                if !fired.contains(&sub) {
+                    fired.push(sub);
+                }
+                *c = to;
+            }
+        }
+    }
+    let s: String = chars.into_iter().collect();
 
     // Strip degree symbols and spaces
     let s: String = s.chars().filter(|c| *c != '°' && *c != ' ').collect();
 
-    // Extract first number-like token (optional minus + digits + optional decimal)
+    // Extract first number-like token (optional minus + digits + optional decimal
+    // + optional exponent when enabled, e.g. for composites such as `{m}e{e}`)
     let mut result = String::new();
     let bytes = s.as_bytes();
     let mut i = 0;
@@ -401,10 +1016,301 @@ pub fn clean_number(text: &str) -> String {
                     i += 1;
                 }
             }
+            if exponent {
+                i += exponent_len(&bytes[i..]);
+            }
             result = s[start..i].to_string();
             break;
         }
         i += 1;
     }
-    result
+    (result, fired)
+}
+
+// ── Decimal point and sign ────────────────────────────────────────────────────
+//
+// The decimal point of an LCD/LED display is a lone blob a fraction of a digit
+// in size, and the engines often drop it (12.34 → 1234) — as they do a minus
+// sign, a short bar.  The crop is binarized (Otsu, ink = minority class) and
+// its connected components are measured against a candidate's digit boxes:
+//   • a dot is a small, squarish blob in the lower part of the digit band,
+//     between the centres of two neighbouring digits;
+//   • a minus is a flat bar at mid-height just left of the first digit, with
+//     no other ink above or below it (unlike the middle segment of a digit
+//     the engine missed).
+// The digit band comes from the ink under the digit boxes, not the boxes
+// themselves: oar-ocr's boxes span the whole crop height.  A dot with a blob
+// of its size straight above it is the lower half of a colon, not a point.
+// A dot is inserted into, or the separator moved to, the gap it sits in; a
+// minus is prepended when the text has none.  Candidates without a box for
+// every digit, or whose symbols don't spell their text, are left alone.
+
+/// Largest decimal point, as a fraction of the digit height.
+const DOT_MAX: f64 = 0.35;
+/// Smallest decimal point; smaller specks are noise.
+const DOT_MIN: f64 = 0.06;
+
+/// Connected ink component of a crop.
+struct Blob {
+    /// `[x, y, width, height]` in crop pixels.
+    bbox: [u32; 4],
+    /// Ink pixels.
+    area: u32,
+}
+
+/// Ink components of `crop` after Otsu binarization.
+fn ink_blobs(crop: &DynamicImage) -> Vec<Blob> {
+    use imageproc::region_labelling::{connected_components, Connectivity};
+
+    let gray = crop.to_luma8();
+    let (w, h) = gray.dimensions();
+    if w == 0 || h == 0 {
+        return Vec::new();
+    }
+    let level = imageproc::contrast::otsu_level(&gray);
+    let dark = gray.pixels().filter(|p| p[0] <= level).count() as u64;
+    let dark_ink = dark * 2 <= w as u64 * h as u64;
+    let binary = GrayImage::from_fn(w, h, |x, y| {
+        let ink = (gray.get_pixel(x, y)[0] <= level) == dark_ink;
+        Luma([if ink { 255 } else { 0 }])
+    });
+    let labels = connected_components(&binary, Connectivity::Eight, Luma([0u8]));
+
+    // label → (min x, min y, max x, max y, pixels)
+    let mut extents: HashMap<u32, (u32, u32, u32, u32, u32)> = HashMap::new();
+    for (x, y, l) in labels.enumerate_pixels() {
+        if l[0] == 0 {
+            continue;
+        }
+        let e = extents.entry(l[0]).or_insert((x, y, x, y, 0));
+        e.0 = e.0.min(x);
+        e.1 = e.1.min(y);
+        e.2 = e.2.max(x);
+        e.3 = e.3.max(y);
+        e.4 += 1;
+    }
+    extents
+        .into_values()
+        .map(|(x0, y0, x1, y1, area)| Blob {
+            bbox: [x0, y0, x1 - x0 + 1, y1 - y0 + 1],
+            area,
+        })
+        .collect()
+}
+
+/// A decimal point found in a crop: the number of digits before it, and its box.
+type Dot = (usize, [u32; 4]);
+
+/// Vertical ink extent `[top, height]` of each digit box that has ink: the
+/// union of the blobs crossing the middle half of the box.
+fn digit_extents(blobs: &[Blob], digits: &[[u32; 4]]) -> Vec<[u32; 2]> {
+    digits
+        .iter()
+        .filter_map(|&[x, _, w, _]| {
+            let (mid0, mid1) = (x + w / 4, x + w - w / 4);
+            let (top, bottom) = blobs
+                .iter()
+                .filter(|b| b.bbox[0] < mid1 && b.bbox[0] + b.bbox[2] > mid0)
+                .map(|b| (b.bbox[1], b.bbox[1] + b.bbox[3]))
+                .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))?;
+            Some([top, bottom - top])
+        })
+        .collect()
+}
+
+/// The decimal point and minus sign among `blobs`, placed against the digit
+/// boxes `digits` (left to right): the dot as the number of digits before it
+/// with its box, and the minus's box.
+fn find_punctuation(blobs: &[Blob], digits: &[[u32; 4]]) -> (Option<Dot>, Option<[u32; 4]>) {
+    let median = |mut v: Vec<u32>| {
+        v.sort_unstable();
+        v[v.len() / 2] as f64
+    };
+    // Digit band from the ink; the boxes' own extent when there is none.
+    let mut extents = digit_extents(blobs, digits);
+    if extents.is_empty() {
+        extents = digits.iter().map(|b| [b[1], b[3]]).collect();
+    }
+    let height = median(extents.iter().map(|e| e[1]).collect());
+    let top = median(extents.iter().map(|e| e[0]).collect());
+    let centre = |b: &[u32; 4]| {
+        (
+            b[0] as f64 + b[2] as f64 / 2.0,
+            b[1] as f64 + b[3] as f64 / 2.0,
+        )
+    };
+
+    let mut dots: Vec<Dot> = vec![];
+    for blob in blobs {
+        let [x, y, w, h] = blob.bbox;
+        let (cx, cy) = centre(&blob.bbox);
+        let (long, short) = (w.max(h) as f64, w.min(h) as f64);
+        let is_dot = long <= DOT_MAX * height
+            && long >= (DOT_MIN * height).max(1.0)
+            && long <= 2.0 * short + 1.0
+            && blob.area as f64 * 2.0 >= (w * h) as f64
+            && cy >= top + 0.65 * height
+            && cy <= top + 1.15 * height;
+        // The lower dot of a colon has its twin straight above it.
+        let colon = blobs.iter().any(|o| {
+            let [ox, oy, ow, oh] = o.bbox;
+            let other_long = ow.max(oh) as f64;
+            !std::ptr::eq(o, blob)
+                && oy + oh <= y
+                && ox < x + w
+                && ox + ow > x
+                && other_long >= 0.5 * long
+                && other_long <= 2.0 * long
+        });
+        if !is_dot || colon {
+            continue;
+        }
+        // Between two digit centres, reaching at most a quarter into the right digit.
+        let gap = digits.windows(2).position(|pair| {
+            cx > centre(&pair[0]).0
+                && cx < centre(&pair[1]).0
+                && x + w <= pair[1][0] + pair[1][2] / 4
+        });
+        if let Some(k) = gap {
+            dots.push((k + 1, blob.bbox));
+        }
+    }
+    // Dots in several gaps (a time, a date) are ambiguous: trust none.
+    let dot = match dots.first() {
+        Some(&(k, b)) if dots.iter().all(|d| d.0 == k) => Some((k, b)),
+        _ => None,
+    };
+
+    let first = digits[0];
+    let in_band = |b: &[u32; 4]| (b[1] as f64) < top + height && (b[1] + b[3]) as f64 > top;
+    let minus = blobs
+        .iter()
+        .filter(|blob| {
+            let [x, _, w, h] = blob.bbox;
+            let cy = centre(&blob.bbox).1;
+            let (wf, hf) = (w as f64, h as f64);
+            x + w <= first[0] + first[2] / 4
+                && first[0] as f64 - (x + w) as f64 <= 2.0 * height
+                && wf >= 0.25 * height
+                && wf <= 1.2 * height
+                && hf <= 0.25 * height
+                && wf >= 2.0 * hf
+                && cy >= top + 0.3 * height
+                && cy <= top + 0.7 * height
+        })
+        .find(|blob| {
+            let [x, _, w, _] = blob.bbox;
+            blobs.iter().all(|o| {
+                std::ptr::eq(o, *blob)
+                    || !in_band(&o.bbox)
+                    || o.bbox[0] >= x + w
+                    || o.bbox[0] + o.bbox[2] <= x
+            })
+        })
+        .map(|b| b.bbox);
+
+    (dot, minus)
+}
+
+/// Restore a dropped or misplaced decimal point and a dropped minus sign in
+/// `r` from the `blobs` of its crop.  The dot is only placed where it leaves
+/// `exp.decimal_places` digits after it, and the minus only when `exp.min`
+/// allows negatives.  Returns the correction as "before→after".
+fn repair_punctuation(
+    r: &mut OcrResult,
+    blobs: &[Blob],
+    exp: &RegionExpectation,
+) -> Option<String> {
+    let before = r.text.trim().to_string();
+    let mut chars: Vec<char> = before.chars().collect();
+    // Symbols must spell the text one character each for positions to line up.
+    if r.symbols.len() != chars.len()
+        || r.symbols
+            .iter()
+            .zip(&chars)
+            .any(|(s, &c)| !s.text.chars().eq(std::iter::once(c)))
+    {
+        return None;
+    }
+    let positions: Vec<usize> = (0..chars.len())
+        .filter(|&i| chars[i].is_ascii_digit())
+        .collect();
+    let boxes: Vec<[u32; 4]> = positions
+        .iter()
+        .map(|&i| r.symbols[i].bbox)
+        .collect::<Option<_>>()?;
+    if boxes.len() < 2 || boxes.windows(2).any(|p| p[0][0] >= p[1][0]) {
+        return None;
+    }
+
+    let (dot, minus) = find_punctuation(blobs, &boxes);
+    let mut symbols = r.symbols.clone();
+    let mark = |text: &str, bbox: [u32; 4]| Symbol {
+        text: text.to_string(),
+        confidence: r.confidence,
+        bbox: Some(bbox),
+    };
+
+    if let Some((k, bbox)) = dot {
+        let fits = exp
+            .decimal_places
+            .map_or(true, |d| d as usize == boxes.len() - k);
+        let after = positions[k - 1];
+        let separators: Vec<usize> = (0..chars.len())
+            .filter(|&i| matches!(chars[i], '.' | ','))
+            .collect();
+        let misplaced = match separators[..] {
+            [] => Some(None),
+            [s] if s != after + 1 => Some(Some(s)),
+            _ => None,
+        };
+        if let (true, Some(remove)) = (fits, misplaced) {
+            let mut at = after + 1;
+            if let Some(s) = remove {
+                chars.remove(s);
+                symbols.remove(s);
+                if s < at {
+                    at -= 1;
+                }
+            }
+            chars.insert(at, '.');
+            symbols.insert(at, mark(".", bbox));
+        }
+    }
+
+    if let Some(bbox) = minus {
+        let first = chars.iter().position(char::is_ascii_digit)?;
+        if exp.min.map_or(true, |m| m < 0.0) && !chars[..first].contains(&'-') {
+            chars.insert(first, '-');
+            symbols.insert(first, mark("-", bbox));
+        }
+    }
+
+    let after: String = chars.into_iter().collect();
+    if after == before {
+        return None;
+    }
+    eprintln!(
+        "[ocr] punctuation: {} {:?} → {:?}",
+        r.engine_name, before, after
+    );
+    r.text = after.clone();
+    r.symbols = symbols;
+    Some(format!("{before}→{after}"))
+}
+
+/// `repair_punctuation` over `results`, recording each repair by engine name.
+/// A `None` expectation (detection off) leaves them untouched.
+fn repair_all(
+    results: &mut [OcrResult],
+    blobs: &[Blob],
+    exp: Option<&RegionExpectation>,
+    repairs: &mut HashMap<String, String>,
+) {
+    let Some(exp) = exp else { return };
+    for r in results.iter_mut() {
+        if let Some(note) = repair_punctuation(r, blobs, exp) {
+            repairs.insert(r.engine_name.clone(), note);
+        }
+    }
 }
@@ -0,0 +1,196 @@
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{OcrResult, Recognizer};
+
+// ── Confidence calibration ────────────────────────────────────────────────────
+//
+// Engines report confidence on different scales: PP-OCR gives a softmax
+// score, Tesseract `mean_text_conf / 100`, the seven-segment decoder a
+// segment margin.  A calibration maps each engine's raw score to the observed
+// probability that its reading is correct, fitted on ground-truth labeled
+// frames, so candidates from different engines can be compared directly.
+
+/// Engines with fewer labeled readings than this are left uncalibrated.
+pub const MIN_SAMPLES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationMethod {
+    /// Monotone step function (pool-adjacent-violators), interpolated linearly.
+    #[default]
+    Isotonic,
+    /// Logistic curve `1 / (1 + exp(a·x + b))` (Platt scaling).
+    Platt,
+}
+
+/// Mapping from one engine's raw confidence to probability of correctness.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum EngineCalibration {
+    /// `(raw, calibrated)` knots in ascending raw order.
+    Isotonic {
+        knots: Vec<(f64, f64)>,
+    },
+    Platt {
+        a: f64,
+        b: f64,
+    },
+}
+
+impl EngineCalibration {
+    /// Fit a mapping from `(raw confidence, reading was correct)` samples.
+    pub fn fit(method: CalibrationMethod, samples: &[(f64, bool)]) -> Self {
+        match method {
+            CalibrationMethod::Isotonic => EngineCalibration::Isotonic {
+                knots: fit_isotonic(samples),
+            },
+            CalibrationMethod::Platt => {
+                let (a, b) = fit_platt(samples);
+                EngineCalibration::Platt { a, b }
+            }
+        }
+    }
+
+    pub fn apply(&self, raw: f64) -> f64 {
+        match self {
+            EngineCalibration::Isotonic { knots } => interpolate(knots, raw),
+            EngineCalibration::Platt { a, b } => 1.0 / (1.0 + (a * raw + b).exp()),
+        }
+    }
+}
+
+/// Per-engine calibrations, keyed by recognizer name (e.g. "tesseract/binary").
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Calibration {
+    pub engines: HashMap<String, EngineCalibration>,
+}
+
+// ── Recognizer wrapper ────────────────────────────────────────────────────────
+
+/// Reports the inner engine's results with calibrated confidences, so the
+/// fast-path threshold and candidate scoring in `read_region` see comparable values.
+pub struct CalibratedRecognizer {
+    pub inner: Box<dyn Recognizer>,
+    pub calibration: EngineCalibration,
+}
+
+impl Recognizer for CalibratedRecognizer {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn recognize(&self, crop: &DynamicImage) -> Option<OcrResult> {
+        Some(self.calibrate(self.inner.recognize(crop)?))
+    }
+
+    // Forwarded so the inner engine keeps its batched inference path.
+    fn recognize_batch(&self, crops: &[DynamicImage]) -> Vec<Option<OcrResult>> {
+        self.inner
+            .recognize_batch(crops)
+            .into_iter()
+            .map(|r| r.map(|r| self.calibrate(r)))
+            .collect()
+    }
+}
+
+impl CalibratedRecognizer {
+    fn calibrate(&self, mut r: OcrResult) -> OcrResult {
+        r.confidence = self.calibration.apply(r.confidence);
+        for s in &mut r.symbols {
+            s.confidence = self.calibration.apply(s.confidence);
+        }
+        r
+    }
+}
+
+// ── Fitting ───────────────────────────────────────────────────────────────────
+
+/// Pool-adjacent-violators: merge neighbouring blocks until the empirical
+/// accuracy is non-decreasing in raw confidence.  Each block becomes one knot
+/// at its mean raw confidence.
+fn fit_isotonic(samples: &[(f64, bool)]) -> Vec<(f64, f64)> {
+    let mut sorted: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|&(raw, ok)| (raw, if ok { 1.0 } else { 0.0 }))
+        .collect();
+    sorted.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    // (sum raw, sum correct, count) per block
+    let mut blocks: Vec<(f64, f64, f64)> = Vec::new();
+    for (raw, y) in sorted {
+        blocks.push((raw, y, 1.0));
+        while blocks.len() > 1 {
+            let (r2, y2, n2) = blocks[blocks.len() - 1];
+            let (r1, y1, n1) = blocks[blocks.len() - 2];
+            if y1 / n1 <= y2 / n2 {
+                break;
+            }
+            blocks.pop();
+            *blocks.last_mut().unwrap() = (r1 + r2, y1 + y2, n1 + n2);
+        }
+    }
+    blocks.into_iter().map(|(r, y, n)| (r / n, y / n)).collect()
+}
+
+/// Linear interpolation between knots, flat beyond the ends.
+fn interpolate(knots: &[(f64, f64)], x: f64) -> f64 {
+    let (Some(first), Some(last)) = (knots.first(), knots.last()) else {
+        return x;
+    };
+    if x <= first.0 {
+        return first.1;
+    }
+    if x >= last.0 {
+        return last.1;
+    }
+    for w in knots.windows(2) {
+        let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+        if x <= x1 {
+            let t = if x1 > x0 { (x - x0) / (x1 - x0) } else { 1.0 };
+            return y0 + (y1 - y0) * t;
+        }
+    }
+    last.1
+}
+
+/// Platt scaling by Newton's method on the regularised targets from Platt (1999).
+fn fit_platt(samples: &[(f64, bool)]) -> (f64, f64) {
+    let pos = samples.iter().filter(|s| s.1).count() as f64;
+    let neg = samples.len() as f64 - pos;
+    let hi = (pos + 1.0) / (pos + 2.0);
+    let lo = 1.0 / (neg + 2.0);
+    let targets: Vec<(f64, f64)> = samples
+        .iter()
+        .map(|&(x, ok)| (x, if ok { hi } else { lo }))
+        .collect();
+
+    let (mut a, mut b) = (0.0f64, ((neg + 1.0) / (pos + 1.0)).ln());
+    for _ in 0..100 {
+        // Gradient and Hessian of the cross-entropy in (a, b).
+        let (mut ga, mut gb, mut haa, mut hab, mut hbb) = (0.0, 0.0, 1e-12, 0.0, 1e-12);
+        for &(x, t) in &targets {
+            let p = 1.0 / (1.0 + (a * x + b).exp());
+            let d = t - p; // d(loss)/d(a·x + b)
+            let w = p * (1.0 - p);
+            ga += d * x;
+            gb += d;
+            haa += w * x * x;
+            hab += w * x;
+            hbb += w;
+        }
+        let det = haa * hbb - hab * hab;
+        if det.abs() < 1e-12 {
+            break;
+        }
+        let da = (hbb * ga - hab * gb) / det;
+        let db = (haa * gb - hab * ga) / det;
+        a -= da;
+        b -= db;
+        if da.abs() < 1e-9 && db.abs() < 1e-9 {
+            break;
+        }
+    }
+    (a, b)
+}
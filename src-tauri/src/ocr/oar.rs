@@ -1,9 +1,14 @@
 use base64::Engine;
 use image::{codecs::png::PngEncoder, imageops::FilterType, DynamicImage, ImageEncoder};
 use oar_ocr::predictors::TextRecognitionPredictor;
-use std::sync::Arc;
+use ort::{session::Session, value::Tensor};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 
-use super::{OcrResult, Recognizer};
+use super::{
+    preprocess::{Geometry, Pipeline},
+    OcrResult, Recognizer, Symbol,
+};
 
 /// Minimum height fed to PaddleOCR v5 mobile (normalises inputs to 48 px internally).
 const MIN_HEIGHT: u32 = 48;
@@ -12,6 +17,13 @@ const MIN_HEIGHT: u32 = 48;
 
 pub struct OarPipeline {
     rec: TextRecognitionPredictor,
+    /// Direct access to the recognition model's per-timestep character
+    /// probabilities (per-symbol results, charset-restricted decoding); plain
+    /// reads keep using `rec`.  `None` if the session could not be created.
+    ctc: Option<CtcModel>,
+    /// Text detector for loosely drawn regions; `None` if the detection model
+    /// is missing or failed to load.
+    det: Option<DetModel>,
 }
 
 // ONNX Runtime sessions are not `Send`/`Sync` by default, but in practice the
@@ -19,14 +31,374 @@ pub struct OarPipeline {
 unsafe impl Send for OarPipeline {}
 unsafe impl Sync for OarPipeline {}
 
-/// Build the recognition pipeline from on-disk ONNX model and dict files.
-pub fn build_pipeline(rec_model: &str, dict: &str) -> Result<OarPipeline, String> {
+/// Build the recognition pipeline from on-disk ONNX model and dict files,
+/// plus the optional detection model.
+pub fn build_pipeline(
+    rec_model: &str,
+    dict: &str,
+    det_model: Option<&str>,
+) -> Result<OarPipeline, String> {
     let rec = TextRecognitionPredictor::builder()
         .dict_path(dict)
         .score_threshold(0.0)
         .build(rec_model)
         .map_err(|e| e.to_string())?;
-    Ok(OarPipeline { rec })
+    let ctc = match CtcModel::load(rec_model, dict) {
+        Ok(m) => Some(m),
+        Err(e) => {
+            eprintln!("[oar] CTC decoding unavailable, using the predictor: {e}");
+            None
+        }
+    };
+    let det = det_model.and_then(|path| match DetModel::load(path) {
+        Ok(m) => Some(m),
+        Err(e) => {
+            eprintln!("[oar] text detection unavailable: {e}");
+            None
+        }
+    });
+    Ok(OarPipeline { rec, ctc, det })
+}
+
+impl OarPipeline {
+    pub fn has_detector(&self) -> bool {
+        self.det.is_some()
+    }
+
+    /// Text boxes in `img`, in its pixel coordinates.
+    /// `None` when no detector is loaded or inference failed.
+    pub fn detect(&self, img: &image::RgbImage) -> Option<Vec<TextBox>> {
+        match self.det.as_ref()?.detect(img) {
+            Ok(boxes) => Some(boxes),
+            Err(e) => {
+                eprintln!("[oar] detection error: {e}");
+                None
+            }
+        }
+    }
+}
+
+// ── Text detection ────────────────────────────────────────────────────────────
+//
+// The PP-OCRv5 detector (a DB network) outputs a per-pixel text probability
+// map.  Connected areas above `DET_THRESHOLD` become boxes; boxes whose mean
+// probability is below `DET_BOX_THRESHOLD` are dropped, and the rest are
+// expanded ("unclipped") because DB is trained on shrunken text areas.
+
+/// The input is scaled so its short side is at least this…
+const DET_MIN_SIDE: u32 = 96;
+/// …and its long side at most this (both then rounded to multiples of 32).
+const DET_MAX_SIDE: u32 = 960;
+const DET_THRESHOLD: f32 = 0.3;
+const DET_BOX_THRESHOLD: f32 = 0.6;
+const DET_UNCLIP_RATIO: f32 = 1.5;
+/// ImageNet normalisation, applied in the model's BGR channel order.
+const DET_MEAN: [f32; 3] = [0.485, 0.456, 0.406];
+const DET_STD: [f32; 3] = [0.229, 0.224, 0.225];
+
+/// A detected text box in crop pixel coordinates.
+#[derive(Debug, Clone, Copy)]
+pub struct TextBox {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Mean text probability inside the box.
+    pub score: f32,
+}
+
+struct DetModel {
+    session: Mutex<Session>,
+}
+
+impl DetModel {
+    fn load(path: &str) -> Result<Self, String> {
+        let session = Session::builder()
+            .and_then(|b| b.commit_from_file(path))
+            .map_err(|e| e.to_string())?;
+        Ok(DetModel {
+            session: Mutex::new(session),
+        })
+    }
+
+    fn detect(&self, img: &image::RgbImage) -> Result<Vec<TextBox>, String> {
+        let (orig_w, orig_h) = img.dimensions();
+        if orig_w == 0 || orig_h == 0 {
+            return Ok(Vec::new());
+        }
+        let scale = (DET_MIN_SIDE as f32 / orig_w.min(orig_h) as f32)
+            .max(1.0)
+            .min(DET_MAX_SIDE as f32 / orig_w.max(orig_h) as f32);
+        let round32 = |v: u32| ((v as f32 * scale / 32.0).round() as u32).max(1) * 32;
+        let (w, h) = (round32(orig_w), round32(orig_h));
+        let resized = image::imageops::resize(img, w, h, FilterType::Triangle);
+
+        let (w, h) = (w as usize, h as usize);
+        let mut data = vec![0f32; 3 * h * w];
+        for (x, y, p) in resized.enumerate_pixels() {
+            let (x, y) = (x as usize, y as usize);
+            for (c, &v) in [p[2], p[1], p[0]].iter().enumerate() {
+                data[c * h * w + y * w + x] = (v as f32 / 255.0 - DET_MEAN[c]) / DET_STD[c];
+            }
+        }
+        let input = Tensor::from_array(([1usize, 3, h, w], data.into_boxed_slice()))
+            .map_err(|e| e.to_string())?;
+
+        let mut session = self.session.lock().map_err(|_| "session poisoned")?;
+        let outputs = session
+            .run(ort::inputs![input])
+            .map_err(|e| e.to_string())?;
+        let (shape, probs) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| e.to_string())?;
+        // Output is [1, 1, H, W] text probabilities.
+        let (map_h, map_w) = match shape.len() {
+            4 => (shape[2] as usize, shape[3] as usize),
+            _ => return Err(format!("unexpected output shape {shape:?}")),
+        };
+        if probs.len() < map_w * map_h {
+            return Err(format!("output too small for shape {shape:?}"));
+        }
+
+        let (sx, sy) = (orig_w as f32 / map_w as f32, orig_h as f32 / map_h as f32);
+        Ok(components(probs, map_w, map_h)
+            .into_iter()
+            .map(|(x0, y0, x1, y1, score)| {
+                let (bw, bh) = ((x1 - x0 + 1) as f32, (y1 - y0 + 1) as f32);
+                let d = bw * bh * DET_UNCLIP_RATIO / (2.0 * (bw + bh));
+                let left = ((x0 as f32 - d) * sx).max(0.0);
+                let top = ((y0 as f32 - d) * sy).max(0.0);
+                let right = ((x1 as f32 + 1.0 + d) * sx).min(orig_w as f32);
+                let bottom = ((y1 as f32 + 1.0 + d) * sy).min(orig_h as f32);
+                TextBox {
+                    x: left as u32,
+                    y: top as u32,
+                    width: (right - left).round().max(1.0) as u32,
+                    height: (bottom - top).round().max(1.0) as u32,
+                    score,
+                }
+            })
+            .collect())
+    }
+}
+
+/// 4-connected components of `probs > DET_THRESHOLD` on a `w`×`h` map, as
+/// `(x0, y0, x1, y1, mean probability)` for those scoring `DET_BOX_THRESHOLD`+.
+fn components(probs: &[f32], w: usize, h: usize) -> Vec<(usize, usize, usize, usize, f32)> {
+    let mut seen = vec![false; w * h];
+    let mut boxes = Vec::new();
+    let mut stack = Vec::new();
+    for start in 0..w * h {
+        if seen[start] || probs[start] <= DET_THRESHOLD {
+            continue;
+        }
+        seen[start] = true;
+        stack.push(start);
+        let (mut x0, mut y0, mut x1, mut y1) = (w, h, 0, 0);
+        let (mut sum, mut n) = (0f32, 0usize);
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+            sum += probs[i];
+            n += 1;
+            let neighbours = [
+                (x > 0).then(|| i - 1),
+                (x + 1 < w).then(|| i + 1),
+                (y > 0).then(|| i - w),
+                (y + 1 < h).then(|| i + w),
+            ];
+            for j in neighbours.into_iter().flatten() {
+                if !seen[j] && probs[j] > DET_THRESHOLD {
+                    seen[j] = true;
+                    stack.push(j);
+                }
+            }
+        }
+        let score = sum / n as f32;
+        // Specks of one or two map pixels are noise.
+        if score >= DET_BOX_THRESHOLD && x1 > x0 && y1 > y0 {
+            boxes.push((x0, y0, x1, y1, score));
+        }
+    }
+    boxes
+}
+
+// ── CTC decoding ──────────────────────────────────────────────────────────────
+//
+// Where per-symbol detail or a charset is needed we run the recognition model
+// ourselves rather than through the predictor, so the per-timestep
+// probabilities are available: each emitted character gets its own confidence
+// and a horizontal extent (the timesteps it spans).  Plain reads stay on the
+// predictor.
+//
+// The predictor also decodes greedily over the full ~18k-symbol dictionary, so
+// a '0' that looks a little like 'O' or '〇' can lose to it.  For regions with
+// a known charset we take, at each timestep, the most probable symbol among
+// blank + the allowed characters only.
+
+/// Input height of PP-OCRv5 recognition models.
+const REC_HEIGHT: u32 = 48;
+
+struct CtcModel {
+    // `Session::run` needs `&mut self`; batches are decoded one at a time per session.
+    session: Mutex<Session>,
+    /// Output class k (k ≥ 1) is `symbols[k - 1]`; class 0 is the CTC blank.
+    symbols: Vec<String>,
+}
+
+/// Model output for a batch: `[n, steps, classes]` probabilities.
+struct CtcOutput {
+    steps: usize,
+    classes: usize,
+    probs: Vec<f32>,
+    /// Padded input width shared by the batch.
+    input_width: usize,
+    /// Each image's width after resizing, before padding.
+    widths: Vec<usize>,
+}
+
+impl CtcModel {
+    fn load(rec_model: &str, dict: &str) -> Result<Self, String> {
+        let session = Session::builder()
+            .and_then(|b| b.commit_from_file(rec_model))
+            .map_err(|e| e.to_string())?;
+        let mut symbols: Vec<String> = std::fs::read_to_string(dict)
+            .map_err(|e| format!("Cannot read {dict}: {e}"))?
+            .lines()
+            .map(str::to_string)
+            .collect();
+        // PP-OCR appends the space character after the dictionary.
+        symbols.push(" ".to_string());
+        Ok(CtcModel {
+            session: Mutex::new(session),
+            symbols,
+        })
+    }
+
+    /// Run the model on a batch of RGB images.  Each is resized to the model
+    /// height keeping aspect, normalised to [-1, 1] in BGR CHW layout, and
+    /// zero-padded on the right to the widest image of the batch.
+    fn probabilities(&self, imgs: &[image::RgbImage]) -> Result<CtcOutput, String> {
+        let h = REC_HEIGHT as usize;
+        let resized: Vec<image::RgbImage> = imgs
+            .iter()
+            .map(|img| {
+                let w = ((img.width() as f32 * REC_HEIGHT as f32 / img.height().max(1) as f32)
+                    .ceil() as u32)
+                    .max(REC_HEIGHT / 4);
+                image::imageops::resize(img, w, REC_HEIGHT, FilterType::Triangle)
+            })
+            .collect();
+        let widths: Vec<usize> = resized.iter().map(|r| r.width() as usize).collect();
+        let w = widths.iter().copied().max().unwrap_or(0);
+
+        let n = resized.len();
+        let mut data = vec![0f32; n * 3 * h * w];
+        for (i, img) in resized.iter().enumerate() {
+            let base = i * 3 * h * w;
+            for (x, y, p) in img.enumerate_pixels() {
+                let (x, y) = (x as usize, y as usize);
+                for (c, &v) in [p[2], p[1], p[0]].iter().enumerate() {
+                    data[base + c * h * w + y * w + x] = (v as f32 / 255.0 - 0.5) / 0.5;
+                }
+            }
+        }
+        let input = Tensor::from_array(([n, 3, h, w], data.into_boxed_slice()))
+            .map_err(|e| e.to_string())?;
+
+        let mut session = self.session.lock().map_err(|_| "session poisoned")?;
+        let outputs = session
+            .run(ort::inputs![input])
+            .map_err(|e| e.to_string())?;
+        let (shape, probs) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| e.to_string())?;
+        // Output is [n, T, C] softmax probabilities.
+        let (steps, classes) = match shape.len() {
+            3 if shape[0] as usize == n => (shape[1] as usize, shape[2] as usize),
+            _ => return Err(format!("unexpected output shape {shape:?}")),
+        };
+        Ok(CtcOutput {
+            steps,
+            classes,
+            probs: probs.to_vec(),
+            input_width: w,
+            widths,
+        })
+    }
+
+    /// Greedy CTC decode of a batch, restricted to `allowed` when given.
+    /// Symbol boxes are in the pixel coordinates of each input image.
+    fn decode(
+        &self,
+        imgs: &[image::RgbImage],
+        allowed: Option<&HashSet<char>>,
+    ) -> Result<Vec<(String, Vec<Symbol>)>, String> {
+        if imgs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let out = self.probabilities(imgs)?;
+        let mask: Option<Vec<bool>> = allowed.map(|allowed| {
+            (0..out.classes)
+                .map(|k| {
+                    k == 0
+                        || self.symbols.get(k - 1).is_some_and(|s| {
+                            let mut chars = s.chars();
+                            matches!((chars.next(), chars.next()), (Some(c), None) if allowed.contains(&c))
+                        })
+                })
+                .collect()
+        });
+
+        let per_image = out.steps * out.classes;
+        Ok(imgs
+            .iter()
+            .enumerate()
+            .map(|(i, img)| {
+                // Input pixels per timestep, scaled back to this image.
+                let step_px = out.input_width as f64 / out.steps.max(1) as f64 * img.width() as f64
+                    / out.widths[i].max(1) as f64;
+                let probs = &out.probs[i * per_image..(i + 1) * per_image];
+
+                let mut text = String::new();
+                let mut symbols: Vec<Symbol> = Vec::new();
+                let mut prev = 0usize;
+                for t in 0..out.steps {
+                    let row = &probs[t * out.classes..(t + 1) * out.classes];
+                    let (best, &p) = row
+                        .iter()
+                        .enumerate()
+                        .filter(|(k, _)| mask.as_ref().map_or(true, |m| m[*k]))
+                        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+                        .unwrap_or((0, &0.0));
+                    if best != 0 && best == prev {
+                        // Same character continues: widen its box, keep its peak probability.
+                        if let Some(s) = symbols.last_mut() {
+                            s.confidence = s.confidence.max(p as f64);
+                            let x1 = ((t + 1) as f64 * step_px).round() as u32;
+                            if let Some(b) = s.bbox.as_mut() {
+                                b[2] = x1.saturating_sub(b[0]).max(1);
+                            }
+                        }
+                    } else if best != 0 {
+                        let x0 = (t as f64 * step_px).round() as u32;
+                        let x1 = ((t + 1) as f64 * step_px).round() as u32;
+                        text.push_str(&self.symbols[best - 1]);
+                        symbols.push(Symbol {
+                            text: self.symbols[best - 1].clone(),
+                            confidence: p as f64,
+                            bbox: Some([x0, 0, x1.saturating_sub(x0).max(1), img.height()]),
+                        });
+                    }
+                    prev = best;
+                }
+                (text, symbols)
+            })
+            .collect())
+    }
 }
 
 // ── Recognizer impl ───────────────────────────────────────────────────────────
@@ -40,19 +412,148 @@ pub enum ColorMode {
 }
 
 pub struct OarRecognizer {
+    /// `oar-ocr/rgb`, `oar-ocr/gray` or `oar-ocr/<preprocess name>`
+    name: String,
     pub pipeline: Arc<OarPipeline>,
     pub color_mode: ColorMode,
+    /// Characters the output is restricted to (see `ocr::allowed_chars`).
+    /// `None` → the full dictionary.
+    pub charset: Option<HashSet<char>>,
+    /// Applied to each crop before `color_mode`; `None` → the crop as-is.
+    pub preprocess: Option<Pipeline>,
+}
+
+impl OarRecognizer {
+    pub fn new(
+        pipeline: Arc<OarPipeline>,
+        color_mode: ColorMode,
+        charset: Option<HashSet<char>>,
+        preprocess: Option<Pipeline>,
+    ) -> Self {
+        let name = match (&preprocess, &color_mode) {
+            (Some(p), _) => format!("oar-ocr/{}", p.name),
+            (None, ColorMode::Rgb) => "oar-ocr/rgb".to_string(),
+            (None, ColorMode::Grayscale) => "oar-ocr/gray".to_string(),
+        };
+        OarRecognizer {
+            name,
+            pipeline,
+            color_mode,
+            charset,
+            preprocess,
+        }
+    }
 }
 
 impl Recognizer for OarRecognizer {
     fn name(&self) -> &str {
-        match self.color_mode {
-            ColorMode::Rgb => "oar-ocr/rgb",
-            ColorMode::Grayscale => "oar-ocr/gray",
-        }
+        &self.name
     }
 
     fn recognize(&self, crop: &DynamicImage) -> Option<OcrResult> {
+        self.recognize_batch(std::slice::from_ref(crop))
+            .pop()
+            .flatten()
+    }
+
+    /// All crops go through each model in one inference call.  A restricted
+    /// charset takes its text and confidence from the CTC model; otherwise the
+    /// predictor reads the text and the CTC model only supplies symbols.
+    fn recognize_batch(&self, crops: &[DynamicImage]) -> Vec<Option<OcrResult>> {
+        let processed: Option<Vec<(DynamicImage, Geometry)>> = self
+            .preprocess
+            .as_ref()
+            .map(|p| crops.iter().map(|c| p.apply(c)).collect());
+        let sources: Vec<&DynamicImage> = match &processed {
+            Some(processed) => processed.iter().map(|(img, _)| img).collect(),
+            None => crops.iter().collect(),
+        };
+        let (images, previews): (Vec<image::RgbImage>, Vec<String>) =
+            sources.iter().map(|c| self.prepare(c)).unzip();
+
+        let decoded: Option<Vec<(String, Vec<Symbol>)>> =
+            self.pipeline.ctc.as_ref().and_then(|ctc| {
+                let decoded = ctc
+                    .decode(&images, self.charset.as_ref())
+                    .map_err(|e| eprintln!("[oar] ctc error: {e}"))
+                    .ok()?;
+                Some(
+                    decoded
+                        .into_iter()
+                        .zip(&images)
+                        .enumerate()
+                        .map(|(i, ((text, mut symbols), img))| {
+                            // Boxes are in model-input pixels; map them back onto the
+                            // preprocessed image, then onto the crop.
+                            let scale = sources[i].width() as f64 / img.width().max(1) as f64;
+                            let geometry = processed.as_ref().map(|p| p[i].1).unwrap_or_default();
+                            for b in symbols.iter_mut().filter_map(|s| s.bbox.as_mut()) {
+                                for v in b.iter_mut() {
+                                    *v = (*v as f64 * scale).round() as u32;
+                                }
+                                *b = geometry.to_crop(*b);
+                            }
+                            (text, symbols)
+                        })
+                        .collect(),
+                )
+            });
+
+        if self.charset.is_some() {
+            if let Some(decoded) = decoded {
+                return decoded
+                    .into_iter()
+                    .zip(previews)
+                    .map(|((text, symbols), preview)| {
+                        let confidence = symbols.iter().map(|s| s.confidence).sum::<f64>()
+                            / symbols.len().max(1) as f64;
+                        eprintln!(
+                            "[oar] {} result: {text:?} conf={confidence:.3} (restricted)",
+                            self.name()
+                        );
+                        self.result(text, confidence, preview, symbols)
+                    })
+                    .collect();
+            }
+            eprintln!(
+                "[oar] {} falling back to the unrestricted predictor",
+                self.name()
+            );
+        }
+
+        let result = match self.pipeline.rec.predict(images) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("[oar] predict error: {e}");
+                return vec![None; crops.len()];
+            }
+        };
+
+        let mut decoded = decoded.unwrap_or_default();
+        let mut scores = result.scores.into_iter();
+        let mut texts = result.texts.into_iter();
+        previews
+            .into_iter()
+            .enumerate()
+            .map(|(i, preview)| {
+                let text = texts.next()?;
+                let score = scores.next().unwrap_or(0.0);
+                eprintln!("[oar] {} result: {:?} conf={score:.3}", self.name(), text);
+                // The CTC pass only lends its symbols when it read the same text.
+                let symbols = decoded
+                    .get_mut(i)
+                    .filter(|(t, _)| *t == text)
+                    .map(|(_, s)| std::mem::take(s))
+                    .unwrap_or_default();
+                self.result(text, score as f64, preview, symbols)
+            })
+            .collect()
+    }
+}
+
+impl OarRecognizer {
+    /// Convert a crop into what the model receives, plus a PNG preview of it.
+    fn prepare(&self, crop: &DynamicImage) -> (image::RgbImage, String) {
         // Prepare the image in the requested colour space
         let img = match self.color_mode {
             ColorMode::Rgb => crop.to_rgb8(),
@@ -97,29 +598,25 @@ impl Recognizer for OarRecognizer {
                 String::new()
             }
         };
+        (img, preview)
+    }
 
-        let result = match self.pipeline.rec.predict(vec![img]) {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("[oar] predict error: {e}");
-                return None;
-            }
-        };
-
-        let text = result.texts.into_iter().next()?;
-        let score = result.scores.into_iter().next().unwrap_or(0.0);
-
-        eprintln!("[oar] {} result: {:?} conf={score:.3}", self.name(), text);
-
+    fn result(
+        &self,
+        text: String,
+        confidence: f64,
+        preview_b64: String,
+        symbols: Vec<Symbol>,
+    ) -> Option<OcrResult> {
         if text.is_empty() {
             return None;
         }
-
         Some(OcrResult {
             text,
-            confidence: score as f64,
-            preview_b64: preview,
+            confidence,
+            preview_b64,
             engine_name: self.name().to_string(),
+            symbols,
         })
     }
 }
@@ -0,0 +1,269 @@
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{encode_png, OcrResult, Recognizer, Symbol};
+
+// ── External recognizer plugins ───────────────────────────────────────────────
+//
+// A plugin is an executable that reads one JSON request per line on stdin and
+// answers each with one JSON line on stdout:
+//
+//   → {"id": 7, "width": 120, "height": 40, "charset": "0123456789.-", "png": "<base64>"}
+//   ← {"id": 7, "text": "12.5", "confidence": 0.93}
+//
+// Replies may add `symbols` (`[{text, confidence, bbox}]`) or report
+// `{"id": 7, "error": "…"}` instead.  Lines on stdout that are not a reply are
+// skipped, and stderr passes through to the app's log.  The process is started
+// on first use and kept for the whole extraction; one that exits or misses
+// the timeout is killed and restarted on the next request.
+
+/// Consecutive failed requests after which a plugin is left stopped.
+const MAX_FAILURES: u32 = 3;
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+/// One configured plugin, listed in `RegionConfig::plugins`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginSpec {
+    /// Engine name is `plugin/<name>`, selectable in `RegionSettings::engines`.
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub stage: PluginStage,
+    /// Per-crop reply timeout.
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+/// Which engine list a plugin joins.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginStage {
+    Priority,
+    #[default]
+    Fallback,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    id: u64,
+    width: u32,
+    height: u32,
+    charset: Option<&'a str>,
+    png: &'a str,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    id: u64,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    confidence: f64,
+    #[serde(default)]
+    symbols: Vec<Symbol>,
+    error: Option<String>,
+}
+
+/// A running plugin process.
+struct Running {
+    child: Child,
+    stdin: ChildStdin,
+    /// Stdout lines, read on a separate thread so replies can time out.
+    lines: Receiver<String>,
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[derive(Default)]
+struct HostState {
+    process: Option<Running>,
+    next_id: u64,
+    failures: u32,
+}
+
+/// One plugin process shared by every engine set that uses it; stopped when
+/// the last set is dropped at the end of the command.
+pub struct PluginHost {
+    spec: PluginSpec,
+    state: Mutex<HostState>,
+}
+
+impl PluginHost {
+    pub fn new(spec: PluginSpec) -> Self {
+        PluginHost {
+            spec,
+            state: Mutex::default(),
+        }
+    }
+
+    fn spawn(&self) -> Result<Running, String> {
+        let mut child = Command::new(&self.spec.command)
+            .args(&self.spec.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("cannot start '{}': {e}", self.spec.command))?;
+        let stdin = child.stdin.take().ok_or("plugin stdin unavailable")?;
+        let stdout = child.stdout.take().ok_or("plugin stdout unavailable")?;
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        eprintln!("[plugin] started '{}' (pid {})", self.spec.name, child.id());
+        Ok(Running {
+            child,
+            stdin,
+            lines,
+        })
+    }
+
+    /// Send one crop and wait for its reply, (re)starting the process as needed.
+    /// Requests are serialised: a plugin handles one crop at a time.
+    fn request(&self, request: Request) -> Result<Reply, String> {
+        let mut state = self.state.lock().map_err(|_| "plugin state poisoned")?;
+        if state.failures >= MAX_FAILURES {
+            return Err("stopped after repeated failures".to_string());
+        }
+        if state.process.is_none() {
+            match self.spawn() {
+                Ok(p) => state.process = Some(p),
+                Err(e) => {
+                    state.failures += 1;
+                    return Err(e);
+                }
+            }
+        }
+        state.next_id += 1;
+        let id = state.next_id;
+        let line = serde_json::to_string(&Request { id, ..request }).map_err(|e| e.to_string())?;
+
+        let timeout = Duration::from_millis(self.spec.timeout_ms);
+        let process = state.process.as_mut().expect("started above");
+        match exchange(process, &line, id, timeout) {
+            Ok(reply) => {
+                state.failures = 0;
+                Ok(reply)
+            }
+            Err(e) => {
+                // Dropping the process kills it; the next request restarts it.
+                state.process = None;
+                state.failures += 1;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Write `line` and wait for the reply carrying `id`, skipping stale replies
+/// from earlier timed-out requests and non-JSON output.
+fn exchange(
+    process: &mut Running,
+    line: &str,
+    id: u64,
+    timeout: Duration,
+) -> Result<Reply, String> {
+    writeln!(process.stdin, "{line}")
+        .and_then(|_| process.stdin.flush())
+        .map_err(|e| format!("write failed: {e}"))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match process.lines.recv_timeout(remaining) {
+            Ok(out) => match serde_json::from_str::<Reply>(&out) {
+                Ok(reply) if reply.id == id => return Ok(reply),
+                Ok(_) => {}
+                Err(_) => eprintln!("[plugin] ignoring output: {out}"),
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(format!("no reply within {} ms", timeout.as_millis()))
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err("process exited".to_string()),
+        }
+    }
+}
+
+/// `Recognizer` backed by a plugin process.
+pub struct PluginRecognizer {
+    pub host: Arc<PluginHost>,
+    /// `plugin/<name>`
+    pub name: String,
+    /// Characters the region may contain, passed on as a hint.
+    pub charset: Option<String>,
+}
+
+impl PluginRecognizer {
+    pub fn new(host: Arc<PluginHost>, charset: Option<String>) -> Self {
+        let name = format!("plugin/{}", host.spec.name);
+        PluginRecognizer {
+            host,
+            name,
+            charset,
+        }
+    }
+
+    pub fn stage(&self) -> PluginStage {
+        self.host.spec.stage
+    }
+}
+
+impl Recognizer for PluginRecognizer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn recognize(&self, crop: &DynamicImage) -> Option<OcrResult> {
+        let rgb = crop.to_rgb8();
+        let png = encode_png(
+            rgb.as_raw(),
+            rgb.width(),
+            rgb.height(),
+            image::ExtendedColorType::Rgb8,
+        );
+        let request = Request {
+            id: 0, // assigned by the host
+            width: rgb.width(),
+            height: rgb.height(),
+            charset: self.charset.as_deref(),
+            png: &png,
+        };
+        let reply = match self.host.request(request) {
+            Ok(reply) => reply,
+            Err(e) => {
+                eprintln!("[plugin] {}: {e}", self.name);
+                return None;
+            }
+        };
+        if let Some(e) = reply.error {
+            eprintln!("[plugin] {}: {e}", self.name);
+            return None;
+        }
+        Some(OcrResult {
+            text: reply.text,
+            confidence: reply.confidence.clamp(0.0, 1.0),
+            preview_b64: png,
+            engine_name: self.name.clone(),
+            symbols: reply.symbols,
+        })
+    }
+}
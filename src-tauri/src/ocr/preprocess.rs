@@ -0,0 +1,826 @@
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+use imageproc::{contrast::otsu_level, distance_transform::Norm, filter::gaussian_blur_f32};
+use serde::{Deserialize, Serialize};
+
+// ── Preprocessing pipelines ───────────────────────────────────────────────
+//
+// A pipeline is a named list of steps applied to a crop before recognition.
+// Steps that need a single channel convert colour input to luma first, so a
+// pipeline's output is grayscale as soon as any such step has run, and RGB
+// otherwise.  Every step's parameters default to the values the built-in
+// presets use, so `{"op": "sauvola"}` is a complete step.
+//
+// Per-region config lists pipelines as preset names or inline definitions:
+//
+//   "preprocess": ["binary", {"name": "soft", "steps": [
+//       {"op": "upscale", "factor": 4}, {"op": "channel", "channel": "g"},
+//       {"op": "invert", "if_mean_below": 140}, {"op": "otsu"}, {"op": "pad"}]}]
+
+/// Largest `upscale` factor; crops are small, so anything beyond only costs memory.
+const MAX_UPSCALE: u32 = 8;
+
+fn default_upscale() -> u32 {
+    6
+}
+fn default_gamma() -> f64 {
+    0.5
+}
+fn default_clahe_tile() -> u32 {
+    32
+}
+fn default_clahe_clip() -> f32 {
+    2.0
+}
+fn default_sigma() -> f32 {
+    1.0
+}
+fn default_sauvola_window() -> u32 {
+    25
+}
+fn default_sauvola_k() -> f64 {
+    0.34
+}
+fn default_sauvola_r() -> f64 {
+    128.0
+}
+fn default_radius() -> u8 {
+    1
+}
+fn default_max_rotation() -> f64 {
+    10.0
+}
+fn default_max_shear() -> f64 {
+    20.0
+}
+fn default_pad() -> u32 {
+    15
+}
+fn default_pad_value() -> u8 {
+    255
+}
+
+/// One preprocessing step.  Serialised with its kind under `"op"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Step {
+    /// Lanczos upscale by an integer factor, at most `MAX_UPSCALE` (helps on
+    /// small crops).
+    Upscale {
+        #[serde(default = "default_upscale")]
+        factor: u32,
+    },
+    /// Reduce to luma or a single RGB channel (coloured LEDs/LCDs).
+    Channel { channel: Channel },
+    /// Invert brightness — only when the mean falls below `if_mean_below`
+    /// (dark background) if given, so text ends up dark on bright.
+    Invert {
+        #[serde(default)]
+        if_mean_below: Option<u8>,
+    },
+    /// Power curve (γ < 1 brightens), only when the mean falls below
+    /// `if_mean_below` if given.
+    Gamma {
+        #[serde(default = "default_gamma")]
+        gamma: f64,
+        #[serde(default)]
+        if_mean_below: Option<u8>,
+    },
+    /// Contrast-limited adaptive histogram equalisation.
+    Clahe {
+        /// Tile size in pixels of the image at this point.
+        #[serde(default = "default_clahe_tile")]
+        tile: u32,
+        /// Clip limit as a multiple of the average bin count.
+        #[serde(default = "default_clahe_clip")]
+        clip_limit: f32,
+    },
+    /// Gaussian blur.
+    Blur {
+        #[serde(default = "default_sigma")]
+        sigma: f32,
+    },
+    /// Sauvola adaptive binarisation.
+    Sauvola {
+        /// Window diameter in pixels (odd).
+        #[serde(default = "default_sauvola_window")]
+        window: u32,
+        /// Sensitivity to local standard deviation (typically 0.2–0.5).
+        #[serde(default = "default_sauvola_k")]
+        k: f64,
+        /// Dynamic range of the standard deviation (128 for 8-bit images).
+        #[serde(default = "default_sauvola_r")]
+        r: f64,
+    },
+    /// Global Otsu binarisation.
+    Otsu,
+    /// Morphological operation on white pixels, `radius` in pixels (L∞).
+    Morphology {
+        /// Named `operation` because `op` is the step's tag.
+        operation: MorphOp,
+        #[serde(default = "default_radius")]
+        radius: u8,
+    },
+    /// Undo rotation and italic shear (see `deskew`).
+    Deskew(Deskew),
+    /// Border of `pixels` around the image in gray level `value`.
+    Pad {
+        #[serde(default = "default_pad")]
+        pixels: u32,
+        #[serde(default = "default_pad_value")]
+        value: u8,
+    },
+}
+
+/// Largest corrections `deskew` searches, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Deskew {
+    #[serde(default = "default_max_rotation")]
+    pub max_rotation: f64,
+    /// Italic slant; LCD fonts typically lean 5–15°.
+    #[serde(default = "default_max_shear")]
+    pub max_shear: f64,
+}
+
+impl Default for Deskew {
+    fn default() -> Self {
+        Deskew {
+            max_rotation: default_max_rotation(),
+            max_shear: default_max_shear(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Luma,
+    R,
+    G,
+    B,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MorphOp {
+    /// Erode then dilate — fills white gaps inside dark strokes.
+    Open,
+    /// Dilate then erode.
+    Close,
+    Erode,
+    Dilate,
+}
+
+/// A named list of steps.  The name becomes the recognizer name suffix
+/// (`tesseract/<name>`, `oar-ocr/<name>`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+/// Where a processed image's pixels lie on the original crop, as the affine
+/// map crop → processed: `x' = m0·x + m1·y + m2`, `y' = m3·x + m4·y + m5`.
+#[derive(Debug, Clone, Copy)]
+pub struct Geometry {
+    m: [f64; 6],
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Geometry {
+            m: [1.0, 0.0, 0.0, 0.0, 1.0, 0.0],
+        }
+    }
+}
+
+impl Geometry {
+    /// Follow the current map with `a`.
+    fn then(&mut self, a: [f64; 6]) {
+        let m = self.m;
+        self.m = [
+            a[0] * m[0] + a[1] * m[3],
+            a[0] * m[1] + a[1] * m[4],
+            a[0] * m[2] + a[1] * m[5] + a[2],
+            a[3] * m[0] + a[4] * m[3],
+            a[3] * m[1] + a[4] * m[4],
+            a[3] * m[2] + a[4] * m[5] + a[5],
+        ];
+    }
+
+    /// Map an `[x, y, w, h]` box on the processed image back onto the crop
+    /// (the bounding box of its mapped corners).
+    pub fn to_crop(&self, b: [u32; 4]) -> [u32; 4] {
+        let m = self.m;
+        let det = m[0] * m[4] - m[1] * m[3];
+        if det.abs() < 1e-12 {
+            return b;
+        }
+        let inverse = |x: f64, y: f64| {
+            let (x, y) = (x - m[2], y - m[5]);
+            ((m[4] * x - m[1] * y) / det, (m[0] * y - m[3] * x) / det)
+        };
+        let (x0, y0) = (b[0] as f64, b[1] as f64);
+        let (x1, y1) = (x0 + b[2] as f64, y0 + b[3] as f64);
+        let corners = [
+            inverse(x0, y0),
+            inverse(x1, y0),
+            inverse(x0, y1),
+            inverse(x1, y1),
+        ];
+        let min_x = corners.iter().map(|c| c.0).fold(f64::INFINITY, f64::min);
+        let min_y = corners.iter().map(|c| c.1).fold(f64::INFINITY, f64::min);
+        let max_x = corners
+            .iter()
+            .map(|c| c.0)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let max_y = corners
+            .iter()
+            .map(|c| c.1)
+            .fold(f64::NEG_INFINITY, f64::max);
+        [
+            min_x.max(0.0) as u32,
+            min_y.max(0.0) as u32,
+            ((max_x - min_x) as u32).max(1),
+            ((max_y - min_y) as u32).max(1),
+        ]
+    }
+}
+
+impl Pipeline {
+    /// Run every step on `crop`.  Returns the processed image (`ImageLuma8`
+    /// or `ImageRgb8`) and how its coordinates relate to the crop.
+    pub fn apply(&self, crop: &DynamicImage) -> (DynamicImage, Geometry) {
+        let mut img = crop.clone();
+        let mut geometry = Geometry::default();
+        for step in &self.steps {
+            img = match *step {
+                Step::Upscale { factor } => {
+                    let factor = factor.clamp(1, MAX_UPSCALE);
+                    let f = factor as f64;
+                    geometry.then([f, 0.0, 0.0, 0.0, f, 0.0]);
+                    img.resize(
+                        img.width() * factor,
+                        img.height() * factor,
+                        image::imageops::FilterType::Lanczos3,
+                    )
+                }
+                Step::Channel { channel } => DynamicImage::ImageLuma8(match channel {
+                    Channel::Luma => img.to_luma8(),
+                    Channel::R => extract_channel(&img, 0),
+                    Channel::G => extract_channel(&img, 1),
+                    Channel::B => extract_channel(&img, 2),
+                }),
+                Step::Invert { if_mean_below } => {
+                    let mut gray = img.to_luma8();
+                    if if_mean_below.map_or(true, |t| mean(&gray) < t as u64) {
+                        for p in gray.pixels_mut() {
+                            p[0] = 255 - p[0];
+                        }
+                    }
+                    DynamicImage::ImageLuma8(gray)
+                }
+                Step::Gamma {
+                    gamma,
+                    if_mean_below,
+                } => {
+                    let mut gray = img.to_luma8();
+                    if if_mean_below.map_or(true, |t| mean(&gray) < t as u64) {
+                        let lut: Vec<u8> = (0u16..=255)
+                            .map(|i| ((i as f64 / 255.0).powf(gamma) * 255.0).round() as u8)
+                            .collect();
+                        for p in gray.pixels_mut() {
+                            p[0] = lut[p[0] as usize];
+                        }
+                    }
+                    DynamicImage::ImageLuma8(gray)
+                }
+                Step::Clahe { tile, clip_limit } => {
+                    DynamicImage::ImageLuma8(clahe(&img.to_luma8(), tile.max(1), clip_limit))
+                }
+                Step::Blur { sigma } if sigma > 0.0 => {
+                    DynamicImage::ImageLuma8(gaussian_blur_f32(&img.to_luma8(), sigma))
+                }
+                Step::Blur { .. } => img,
+                Step::Sauvola { window, k, r } => {
+                    DynamicImage::ImageLuma8(sauvola_threshold(&img.to_luma8(), window, k, r))
+                }
+                Step::Otsu => {
+                    let mut gray = img.to_luma8();
+                    let level = otsu_level(&gray);
+                    for p in gray.pixels_mut() {
+                        p[0] = if p[0] > level { 255 } else { 0 };
+                    }
+                    DynamicImage::ImageLuma8(gray)
+                }
+                Step::Morphology { operation, radius } => {
+                    use imageproc::morphology::{close, dilate, erode, open};
+                    let gray = img.to_luma8();
+                    DynamicImage::ImageLuma8(match operation {
+                        MorphOp::Open => open(&gray, Norm::LInf, radius),
+                        MorphOp::Close => close(&gray, Norm::LInf, radius),
+                        MorphOp::Erode => erode(&gray, Norm::LInf, radius),
+                        MorphOp::Dilate => dilate(&gray, Norm::LInf, radius),
+                    })
+                }
+                Step::Deskew(limits) => {
+                    let (corrected, transform, _) = deskew(&img, limits);
+                    geometry.then(transform);
+                    corrected
+                }
+                Step::Pad { pixels, value } => {
+                    let p = pixels as f64;
+                    geometry.then([1.0, 0.0, p, 0.0, 1.0, p]);
+                    pad(&img, pixels, value)
+                }
+            };
+        }
+        (img, geometry)
+    }
+}
+
+// ── Presets ───────────────────────────────────────────────────────────────
+
+/// The built-in pipelines, selectable by name.
+/// Serialised in snake_case (`"channel_r"`) for per-region settings.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preprocess {
+    /// Full pipeline: upscale → luma → invert → gamma → CLAHE → blur → Sauvola → morph-open.
+    Binary,
+    /// Same pipeline as `Binary` but stops at enhanced grayscale (no binarization).
+    Gray,
+    /// Raw luma only — minimal processing, fast baseline.
+    RawGray,
+    /// Raw RGB, no grayscale conversion.
+    RawRgb,
+    /// Extract the red channel, then apply the full Binary pipeline.
+    ChannelR,
+    /// Extract the green channel, then apply the full Binary pipeline.
+    ChannelG,
+    /// Extract the blue channel, then apply the full Binary pipeline.
+    ChannelB,
+}
+
+impl Preprocess {
+    pub fn name(self) -> &'static str {
+        match self {
+            Preprocess::Binary => "binary",
+            Preprocess::Gray => "gray",
+            Preprocess::RawGray => "raw-gray",
+            Preprocess::RawRgb => "rgb",
+            Preprocess::ChannelR => "channel-r",
+            Preprocess::ChannelG => "channel-g",
+            Preprocess::ChannelB => "channel-b",
+        }
+    }
+
+    /// The preset as a pipeline.
+    ///
+    /// Enhanced presets:
+    ///   1. Lanczos 6× upscale
+    ///   2. Grayscale (or single RGB-channel extraction for Channel* modes)
+    ///   3. Auto-invert if background is dark (text dark on bright, as Tesseract & Sauvola expect)
+    ///   4. Gamma correction if the image is still underexposed after inversion
+    ///   5. CLAHE — tiles of ~5 original pixels at 6×
+    ///   6. σ=1 Gaussian blur — removes CLAHE quantization artifacts
+    ///   7. Sauvola adaptive binarization (Binary / Channel* only)
+    ///   8. Morphological opening — reconnects broken segments of '8', '0', '1'
+    ///   9. White border padding to help Tesseract find the text block
+    pub fn pipeline(self) -> Pipeline {
+        let channel = match self {
+            Preprocess::RawRgb => None,
+            Preprocess::ChannelR => Some(Channel::R),
+            Preprocess::ChannelG => Some(Channel::G),
+            Preprocess::ChannelB => Some(Channel::B),
+            _ => Some(Channel::Luma),
+        };
+        let mut steps = Vec::new();
+        let enhanced = !matches!(self, Preprocess::RawGray | Preprocess::RawRgb);
+        if enhanced {
+            steps.push(Step::Upscale {
+                factor: default_upscale(),
+            });
+        }
+        if let Some(channel) = channel {
+            steps.push(Step::Channel { channel });
+        }
+        if enhanced {
+            steps.extend([
+                Step::Invert {
+                    if_mean_below: Some(140),
+                },
+                Step::Gamma {
+                    gamma: default_gamma(),
+                    if_mean_below: Some(80),
+                },
+                Step::Clahe {
+                    tile: default_clahe_tile(),
+                    clip_limit: default_clahe_clip(),
+                },
+                Step::Blur {
+                    sigma: default_sigma(),
+                },
+            ]);
+            if self != Preprocess::Gray {
+                steps.extend([
+                    Step::Sauvola {
+                        window: default_sauvola_window(),
+                        k: default_sauvola_k(),
+                        r: default_sauvola_r(),
+                    },
+                    Step::Morphology {
+                        operation: MorphOp::Open,
+                        radius: default_radius(),
+                    },
+                ]);
+            }
+            steps.push(Step::Pad {
+                pixels: default_pad(),
+                value: default_pad_value(),
+            });
+        }
+        Pipeline {
+            name: self.name().to_string(),
+            steps,
+        }
+    }
+}
+
+/// A preset name or an inline pipeline, as listed in `RegionSettings`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PipelineSpec {
+    Preset(Preprocess),
+    Custom(Pipeline),
+}
+
+impl PipelineSpec {
+    pub fn resolve(&self) -> Pipeline {
+        match self {
+            PipelineSpec::Preset(p) => p.pipeline(),
+            PipelineSpec::Custom(p) => p.clone(),
+        }
+    }
+}
+
+// ── Deskew ────────────────────────────────────────────────────────────────────
+//
+// Projection-profile estimation on the Otsu-binarised image:
+//   1. Rotation — the angle whose row profile of ink pixels is sharpest
+//      (text lines and segment rows line up).
+//   2. Shear — after rotating, the horizontal slant whose column profile is
+//      sharpest (italic strokes become vertical).
+// Both are undone in one affine warp about the image centre; the background
+// fills the uncovered corners.
+
+/// Search step for the rotation angle, in degrees.
+const ROTATION_STEP: f64 = 0.5;
+/// Search step for the shear angle, in degrees.
+const SHEAR_STEP: f64 = 1.0;
+/// Corrections below this many degrees are not applied.
+const MIN_CORRECTION: f64 = 0.25;
+/// Ink pixels sampled for the profiles (larger images are subsampled).
+const MAX_INK_SAMPLES: usize = 20_000;
+
+/// Correction applied by `deskew`, in degrees.  Positive rotation turns the
+/// content clockwise; positive shear moves its top to the left (straightening
+/// right-leaning italics).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Skew {
+    pub rotation: f64,
+    pub shear: f64,
+}
+
+/// Estimate and undo rotation and italic shear.  Returns the corrected image
+/// (grayscale input stays grayscale, anything else is warped as RGB), the
+/// affine map input → output and the correction.  Below `MIN_CORRECTION` the
+/// input is returned unchanged with a zero correction.
+pub fn deskew(img: &DynamicImage, limits: Deskew) -> (DynamicImage, [f64; 6], Skew) {
+    use imageproc::geometric_transformations::{warp, Interpolation, Projection};
+
+    let gray = img.to_luma8();
+    let identity = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+    let unchanged = || (img.clone(), identity, Skew::default());
+    let Some((ink, background)) = ink_points(&gray) else {
+        return unchanged();
+    };
+    let (cx, cy) = (gray.width() as f64 / 2.0, gray.height() as f64 / 2.0);
+    let centred: Vec<(f64, f64)> = ink.iter().map(|&(x, y)| (x - cx, y - cy)).collect();
+
+    let rotation = best_angle(limits.max_rotation, ROTATION_STEP, |deg| {
+        let (sin, cos) = deg.to_radians().sin_cos();
+        profile_score(centred.iter().map(|&(x, y)| x * sin + y * cos))
+    });
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let rotated: Vec<(f64, f64)> = centred
+        .iter()
+        .map(|&(x, y)| (x * cos - y * sin, x * sin + y * cos))
+        .collect();
+    let shear = best_angle(limits.max_shear, SHEAR_STEP, |deg| {
+        let t = deg.to_radians().tan();
+        profile_score(rotated.iter().map(|&(x, y)| x + y * t))
+    });
+
+    if rotation.abs() < MIN_CORRECTION && shear.abs() < MIN_CORRECTION {
+        return unchanged();
+    }
+    eprintln!("[deskew] rotation={rotation:.1}° shear={shear:.1}°");
+
+    // p' = Sh · R · (p − c) + c
+    let t = shear.to_radians().tan();
+    let (a, b, d, e) = (cos + t * sin, -sin + t * cos, sin, cos);
+    let transform = [a, b, cx - a * cx - b * cy, d, e, cy - d * cx - e * cy];
+    let m = transform.map(|v| v as f32);
+    let Some(projection) =
+        Projection::from_matrix([m[0], m[1], m[2], m[3], m[4], m[5], 0.0, 0.0, 1.0])
+    else {
+        return unchanged();
+    };
+    let corrected = match img {
+        DynamicImage::ImageLuma8(_) => DynamicImage::ImageLuma8(warp(
+            &gray,
+            &projection,
+            Interpolation::Bilinear,
+            Luma([background]),
+        )),
+        _ => DynamicImage::ImageRgb8(warp(
+            &img.to_rgb8(),
+            &projection,
+            Interpolation::Bilinear,
+            Rgb([background; 3]),
+        )),
+    };
+    (corrected, transform, Skew { rotation, shear })
+}
+
+/// Coordinates of ink pixels (the minority Otsu class) and the background
+/// gray level.  `None` when the image has no contrast.
+fn ink_points(gray: &GrayImage) -> Option<(Vec<(f64, f64)>, u8)> {
+    let level = otsu_level(gray);
+    let dark = gray.pixels().filter(|p| p[0] <= level).count();
+    let total = (gray.width() * gray.height()) as usize;
+    if dark == 0 || dark == total {
+        return None;
+    }
+    let ink_dark = dark * 2 <= total;
+    let ink_count = if ink_dark { dark } else { total - dark };
+    let stride = ((ink_count as f64 / MAX_INK_SAMPLES as f64).sqrt().ceil() as u32).max(1);
+    let mut points = Vec::new();
+    for y in (0..gray.height()).step_by(stride as usize) {
+        for x in (0..gray.width()).step_by(stride as usize) {
+            if (gray.get_pixel(x, y)[0] <= level) == ink_dark {
+                points.push((x as f64, y as f64));
+            }
+        }
+    }
+    let background = if ink_dark { 255 } else { 0 };
+    Some((points, background))
+}
+
+/// Sum of squared bin counts of a one-pixel histogram of `values`: largest
+/// when the values pile up on few bins.
+fn profile_score(values: impl Iterator<Item = f64>) -> f64 {
+    let mut bins: std::collections::HashMap<i64, f64> = std::collections::HashMap::new();
+    for v in values {
+        *bins.entry(v.round() as i64).or_default() += 1.0;
+    }
+    bins.values().map(|c| c * c).sum()
+}
+
+/// The angle in `[-max, max]` (degrees, in `step`s) with the highest score;
+/// ties go to the smaller correction.
+fn best_angle(max: f64, step: f64, score: impl Fn(f64) -> f64) -> f64 {
+    let steps = (max.max(0.0) / step).floor() as i64;
+    let mut best = (0.0, score(0.0));
+    for i in 1..=steps {
+        for deg in [i as f64 * step, -(i as f64) * step] {
+            let s = score(deg);
+            if s > best.1 {
+                best = (deg, s);
+            }
+        }
+    }
+    best.0
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────
+
+fn mean(gray: &GrayImage) -> u64 {
+    let count = (gray.width() * gray.height()).max(1) as u64;
+    gray.pixels().map(|p| p[0] as u64).sum::<u64>() / count
+}
+
+/// Extract a single RGB channel (0=R, 1=G, 2=B) as a grayscale image.
+fn extract_channel(img: &DynamicImage, channel: usize) -> GrayImage {
+    let rgb = img.to_rgb8();
+    let (w, h) = rgb.dimensions();
+    let mut gray = GrayImage::new(w, h);
+    for (x, y, pixel) in rgb.enumerate_pixels() {
+        gray.put_pixel(x, y, Luma([pixel[channel]]));
+    }
+    gray
+}
+
+fn pad(img: &DynamicImage, pixels: u32, value: u8) -> DynamicImage {
+    let (w, h) = (
+        img.width().saturating_add(pixels * 2),
+        img.height().saturating_add(pixels * 2),
+    );
+    let at = pixels as i64;
+    match img {
+        DynamicImage::ImageLuma8(gray) => {
+            let mut out = GrayImage::from_pixel(w, h, Luma([value]));
+            image::imageops::overlay(&mut out, gray, at, at);
+            DynamicImage::ImageLuma8(out)
+        }
+        _ => {
+            let mut out = RgbImage::from_pixel(w, h, Rgb([value; 3]));
+            image::imageops::overlay(&mut out, &img.to_rgb8(), at, at);
+            DynamicImage::ImageRgb8(out)
+        }
+    }
+}
+
+// ── CLAHE ─────────────────────────────────────────────────────────────────
+//
+// Contrast-Limited Adaptive Histogram Equalization (Zuiderveld 1994).
+//
+// Algorithm:
+//   1. Divide image into a grid of non-overlapping tiles.
+//   2. Build each tile's 256-bin histogram.
+//   3. Clip bins exceeding clip_limit_factor × (tile_area/256) and redistribute excess.
+//   4. Compute CDF-based tone mapping for each tile.
+//   5. Apply per-pixel mapping via bilinear interpolation between the four nearest tile centres.
+
+fn clahe(img: &GrayImage, tile_size: u32, clip_limit_factor: f32) -> GrayImage {
+    let width = img.width();
+    let height = img.height();
+    let tiles_x = (width + tile_size - 1) / tile_size;
+    let tiles_y = (height + tile_size - 1) / tile_size;
+
+    // Pre-compute tone mapping for every tile
+    let maps: Vec<Vec<[u8; 256]>> = (0..tiles_y)
+        .map(|ty| {
+            (0..tiles_x)
+                .map(|tx| tile_mapping(img, tx, ty, tile_size, clip_limit_factor))
+                .collect()
+        })
+        .collect();
+
+    // Apply bilinearly-interpolated mapping to every pixel
+    let mut out = GrayImage::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = img.get_pixel(x, y)[0];
+            let v = bilinear_clahe(&maps, pixel, x, y, tile_size, tiles_x, tiles_y);
+            out.put_pixel(x, y, Luma([v]));
+        }
+    }
+    out
+}
+
+/// Build the input → output tone mapping [0..255] for one CLAHE tile.
+fn tile_mapping(
+    img: &GrayImage,
+    tx: u32,
+    ty: u32,
+    tile_size: u32,
+    clip_limit_factor: f32,
+) -> [u8; 256] {
+    let w = img.width();
+    let h = img.height();
+    let x0 = tx * tile_size;
+    let y0 = ty * tile_size;
+    let x1 = ((tx + 1) * tile_size).min(w);
+    let y1 = ((ty + 1) * tile_size).min(h);
+    let tile_area = ((x1 - x0) * (y1 - y0)) as u64;
+
+    // Histogram
+    let mut hist = [0u64; 256];
+    for py in y0..y1 {
+        for px in x0..x1 {
+            hist[img.get_pixel(px, py)[0] as usize] += 1;
+        }
+    }
+
+    // Clip and redistribute excess uniformly
+    let clip = ((tile_area as f32 / 256.0) * clip_limit_factor).max(1.0) as u64;
+    let mut excess = 0u64;
+    for h in hist.iter_mut() {
+        if *h > clip {
+            excess += *h - clip;
+            *h = clip;
+        }
+    }
+    let per_bin = excess / 256;
+    let leftover = (excess % 256) as usize;
+    for (i, h) in hist.iter_mut().enumerate() {
+        *h += per_bin + if i < leftover { 1 } else { 0 };
+    }
+
+    // CDF → normalised tone mapping
+    let mut mapping = [0u8; 256];
+    let mut cdf = 0u64;
+    for (i, &h) in hist.iter().enumerate() {
+        cdf += h;
+        mapping[i] = ((cdf * 255) / tile_area).min(255) as u8;
+    }
+    mapping
+}
+
+/// Bilinear interpolation between the four tile mappings nearest to pixel (x, y).
+fn bilinear_clahe(
+    maps: &[Vec<[u8; 256]>],
+    pixel: u8,
+    x: u32,
+    y: u32,
+    tile_size: u32,
+    tiles_x: u32,
+    tiles_y: u32,
+) -> u8 {
+    let ts = tile_size as f32;
+    let half = ts / 2.0;
+
+    // Fractional tile coordinates: 0.0 = centre of tile 0, 1.0 = centre of tile 1, …
+    let tx_f = (x as f32 - half) / ts;
+    let ty_f = (y as f32 - half) / ts;
+
+    let tx0 = (tx_f.floor() as i32).clamp(0, tiles_x as i32 - 1) as usize;
+    let ty0 = (ty_f.floor() as i32).clamp(0, tiles_y as i32 - 1) as usize;
+    let tx1 = (tx0 + 1).min(tiles_x as usize - 1);
+    let ty1 = (ty0 + 1).min(tiles_y as usize - 1);
+
+    let fx = (tx_f - tx0 as f32).clamp(0.0, 1.0);
+    let fy = (ty_f - ty0 as f32).clamp(0.0, 1.0);
+
+    let v00 = maps[ty0][tx0][pixel as usize] as f32;
+    let v10 = maps[ty0][tx1][pixel as usize] as f32;
+    let v01 = maps[ty1][tx0][pixel as usize] as f32;
+    let v11 = maps[ty1][tx1][pixel as usize] as f32;
+
+    let top = v00 + (v10 - v00) * fx;
+    let bot = v01 + (v11 - v01) * fx;
+    (top + (bot - top) * fy).round() as u8
+}
+
+// ── Sauvola adaptive threshold ────────────────────────────────────────────
+//
+// T(x,y) = mean(x,y) × (1 + k × (σ(x,y)/R − 1))
+//
+// Uses summed-area tables (integral images) for O(1) per-pixel local statistics.
+// Thresholds adapt to local background variations that a global threshold
+// would miss (gradients, glare, uneven display backlighting).
+
+fn sauvola_threshold(gray: &GrayImage, window: u32, k: f64, r: f64) -> GrayImage {
+    let w = gray.width() as usize;
+    let h = gray.height() as usize;
+    let half = (window / 2) as usize;
+    let stride = w + 1;
+
+    // Integral images: row-major, (h+1) × (w+1), zero-padded top and left border
+    let mut isum = vec![0i64; stride * (h + 1)];
+    let mut isumsq = vec![0i64; stride * (h + 1)];
+
+    for y in 0..h {
+        for x in 0..w {
+            let v = gray.get_pixel(x as u32, y as u32)[0] as i64;
+            let idx = (y + 1) * stride + (x + 1);
+            let above = y * stride + (x + 1);
+            let left = (y + 1) * stride + x;
+            let diag = y * stride + x;
+            isum[idx] = v + isum[above] + isum[left] - isum[diag];
+            isumsq[idx] = v * v + isumsq[above] + isumsq[left] - isumsq[diag];
+        }
+    }
+
+    let mut out = GrayImage::new(w as u32, h as u32);
+    for y in 0..h {
+        for x in 0..w {
+            let x0 = x.saturating_sub(half);
+            let y0 = y.saturating_sub(half);
+            let x1 = (x + half + 1).min(w);
+            let y1 = (y + half + 1).min(h);
+            let count = ((x1 - x0) * (y1 - y0)) as i64;
+
+            let br = y1 * stride + x1;
+            let bl = y1 * stride + x0;
+            let tr = y0 * stride + x1;
+            let tl = y0 * stride + x0;
+            let sum = isum[br] - isum[bl] - isum[tr] + isum[tl];
+            let sumsq = isumsq[br] - isumsq[bl] - isumsq[tr] + isumsq[tl];
+
+            let mean = sum as f64 / count as f64;
+            let var = (sumsq as f64 / count as f64) - mean * mean;
+            let std = var.max(0.0).sqrt();
+
+            let threshold = mean * (1.0 + k * (std / r - 1.0));
+            let pv = gray.get_pixel(x as u32, y as u32)[0] as f64;
+            out.put_pixel(
+                x as u32,
+                y as u32,
+                Luma([if pv >= threshold { 255 } else { 0 }]),
+            );
+        }
+    }
+    out
+}
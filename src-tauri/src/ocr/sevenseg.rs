@@ -0,0 +1,515 @@
+use image::DynamicImage;
+
+use super::{encode_png, OcrResult, Recognizer, Symbol};
+
+// ── Seven-segment decoding ────────────────────────────────────────────────────
+//
+// Instead of reading glyph shapes, this recognizer measures how much "ink"
+// (lit LED / dark LCD) each of the seven segment zones of a digit cell holds:
+//
+//      aaa
+//     f   b
+//      ggg
+//     e   c
+//      ddd
+//
+// Steps:
+//   1. Ink map — brightness normalised to [0, 1] with the display's polarity
+//      detected automatically (bright-on-dark LEDs vs dark-on-bright LCDs).
+//   2. Layout — Otsu on the ink map, column projection → runs of inked
+//      columns.  Short runs near the baseline are decimal points, short runs
+//      mid-height are minus signs, the rest are digit cells.
+//   3. Narrow cells (the digit 1 only lights the right-hand segments) are
+//      widened leftwards to the typical digit width so the zones line up.
+//   4. Zone sampling — mean ink per segment zone.
+//   5. Lit / unlit split — one threshold across all zones of the display, so
+//      ghosted (faintly visible unlit) segments fall on the unlit side.
+//   6. Segment table lookup; the margin of the least certain segment becomes
+//      the digit's confidence.
+
+/// Segment bits.
+const A: u8 = 1 << 0;
+const B: u8 = 1 << 1;
+const C: u8 = 1 << 2;
+const D: u8 = 1 << 3;
+const E: u8 = 1 << 4;
+const F: u8 = 1 << 5;
+const G: u8 = 1 << 6;
+
+/// Segment zones in cell-relative coordinates `(u0, v0, u1, v1)`, in bit order a…g.
+const ZONES: [(f32, f32, f32, f32); 7] = [
+    (0.30, 0.00, 0.70, 0.14), // a
+    (0.72, 0.18, 1.00, 0.40), // b
+    (0.72, 0.60, 1.00, 0.82), // c
+    (0.30, 0.86, 0.70, 1.00), // d
+    (0.00, 0.60, 0.28, 0.82), // e
+    (0.00, 0.18, 0.28, 0.40), // f
+    (0.30, 0.43, 0.70, 0.57), // g
+];
+
+/// Segment patterns, including the common alternative forms of 6, 7 and 9.
+const PATTERNS: &[(u8, char)] = &[
+    (A | B | C | D | E | F, '0'),
+    (B | C, '1'),
+    (A | B | D | E | G, '2'),
+    (A | B | C | D | G, '3'),
+    (B | C | F | G, '4'),
+    (A | C | D | F | G, '5'),
+    (A | C | D | E | F | G, '6'),
+    (C | D | E | F | G, '6'),
+    (A | B | C, '7'),
+    (A | B | C | F, '7'),
+    (A | B | C | D | E | F | G, '8'),
+    (A | B | C | D | F | G, '9'),
+    (A | B | C | F | G, '9'),
+    (G, '-'),
+];
+
+/// Confidence multiplier for a pattern one segment away from a table entry.
+const NEAR_MATCH_PENALTY: f64 = 0.5;
+/// Minimum spread between lit and unlit zone means for the split to be trusted.
+const MIN_CONTRAST: f32 = 0.15;
+/// Width of a digit cell relative to text height when no full-width digit is
+/// visible (e.g. the reading "11").
+const DEFAULT_ASPECT: f32 = 0.55;
+/// Cells narrower than this fraction of the typical digit width are right-aligned
+/// and widened (the 1 lights only segments b and c).
+const NARROW_CELL: f32 = 0.6;
+/// Runs no taller than this fraction of the text height are a decimal point or
+/// minus sign rather than a digit.
+const SHORT_RUN: f32 = 0.3;
+/// Smallest crop (in pixels) worth analysing.
+const MIN_SIZE: usize = 8;
+
+pub struct SevenSegRecognizer;
+
+impl Recognizer for SevenSegRecognizer {
+    fn name(&self) -> &str {
+        "sevenseg"
+    }
+
+    fn recognize(&self, crop: &DynamicImage) -> Option<OcrResult> {
+        let ink = InkMap::from_image(crop)?;
+        let layout = ink.layout()?;
+
+        // Sample every digit cell first so the lit/unlit threshold sees the whole display.
+        let samples: Vec<[f32; 7]> = layout
+            .cells
+            .iter()
+            .filter_map(|c| match c.kind {
+                CellKind::Digit => Some(ink.sample_zones(c.x0, c.x1, layout.y0, layout.y1)),
+                _ => None,
+            })
+            .collect();
+        let (threshold, spread) = split_levels(samples.iter().flatten().copied())?;
+
+        let mut text = String::new();
+        let mut symbols = Vec::new();
+        let mut samples = samples.into_iter();
+        for cell in &layout.cells {
+            let (ch, conf) = match cell.kind {
+                CellKind::Digit => {
+                    let zones = samples.next()?;
+                    match decode_digit(&zones, threshold, spread) {
+                        DigitDecode::Blank => continue,
+                        DigitDecode::Unknown => {
+                            eprintln!("[sevenseg] undecodable digit cell at x={}", cell.x0);
+                            return None;
+                        }
+                        DigitDecode::Char(ch, conf) => (ch, conf),
+                    }
+                }
+                CellKind::Point => {
+                    // A point before any digit is noise, not a leading ".5".
+                    if text.is_empty() || text.ends_with('.') {
+                        continue;
+                    }
+                    ('.', cell.strength as f64)
+                }
+                CellKind::Minus => {
+                    if !text.is_empty() {
+                        continue;
+                    }
+                    ('-', cell.strength as f64)
+                }
+            };
+            text.push(ch);
+            symbols.push(Symbol {
+                text: ch.to_string(),
+                confidence: conf.clamp(0.0, 1.0),
+                bbox: Some(layout.bbox(cell)),
+            });
+        }
+
+        let text = text.trim_end_matches('.').to_string();
+        symbols.truncate(text.chars().count());
+        if !text.chars().any(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let confidence = symbols.iter().map(|s| s.confidence).fold(1.0f64, f64::min);
+        let digits: Vec<f64> = symbols.iter().map(|s| s.confidence).collect();
+
+        eprintln!("[sevenseg] result: {text:?} conf={confidence:.3} digits={digits:.2?}");
+
+        Some(OcrResult {
+            text,
+            confidence,
+            preview_b64: ink.preview(),
+            engine_name: self.name().to_string(),
+            symbols,
+        })
+    }
+}
+
+// ── Ink map ───────────────────────────────────────────────────────────────────
+
+/// Per-pixel ink in [0, 1]: 1 = fully lit segment, 0 = background.
+pub(super) struct InkMap {
+    pub width: usize,
+    pub height: usize,
+    pub ink: Vec<f32>,
+}
+
+impl InkMap {
+    /// Build the ink map, detecting polarity.  Brightness is the maximum of
+    /// the RGB channels so red and green LEDs stay bright.
+    pub fn from_image(crop: &DynamicImage) -> Option<Self> {
+        let rgb = crop.to_rgb8();
+        let (width, height) = (rgb.width() as usize, rgb.height() as usize);
+        if width < MIN_SIZE / 2 || height < MIN_SIZE {
+            return None;
+        }
+        let bright: Vec<u8> = rgb.pixels().map(|p| p[0].max(p[1]).max(p[2])).collect();
+
+        let mut hist = [0u32; 256];
+        for &v in &bright {
+            hist[v as usize] += 1;
+        }
+        // Robust black/white points (2nd and 98th percentile).
+        let n = bright.len() as u32;
+        let lo = percentile(&hist, n / 50);
+        let hi = percentile(&hist, n - n / 50 - 1);
+        if hi <= lo {
+            return None;
+        }
+
+        // Segments cover less area than background: the minority side of the
+        // Otsu split is the ink.
+        let t = otsu(&hist);
+        let above: u32 = hist[t as usize + 1..].iter().sum();
+        let bright_ink = above < n - above;
+
+        let range = (hi - lo) as f32;
+        let ink = bright
+            .iter()
+            .map(|&v| {
+                let x = ((v as f32 - lo as f32) / range).clamp(0.0, 1.0);
+                if bright_ink {
+                    x
+                } else {
+                    1.0 - x
+                }
+            })
+            .collect();
+        Some(InkMap { width, height, ink })
+    }
+
+    fn at(&self, x: usize, y: usize) -> f32 {
+        self.ink[y * self.width + x]
+    }
+
+    /// Otsu-binarised ink, used for layout only.
+    fn mask(&self) -> Vec<bool> {
+        let mut hist = [0u32; 256];
+        for &v in &self.ink {
+            hist[(v * 255.0) as usize] += 1;
+        }
+        let t = otsu(&hist) as f32 / 255.0;
+        self.ink.iter().map(|&v| v > t).collect()
+    }
+
+    /// Locate the text band and the cells within it.
+    pub fn layout(&self) -> Option<Layout> {
+        let mask = self.mask();
+        let (w, h) = (self.width, self.height);
+
+        // Vertical extent: rows holding more than a speck of ink.
+        let row_min = (w / 50).max(1);
+        let rows: Vec<usize> = (0..h)
+            .filter(|&y| mask[y * w..(y + 1) * w].iter().filter(|&&m| m).count() >= row_min)
+            .collect();
+        let (y0, y1) = (*rows.first()?, *rows.last()? + 1);
+        let text_h = (y1 - y0) as f32;
+        if (text_h as usize) < MIN_SIZE / 2 {
+            return None;
+        }
+
+        // Column runs, bridging hairline gaps between touching segments.
+        let col_inked: Vec<bool> = (0..w).map(|x| (y0..y1).any(|y| mask[y * w + x])).collect();
+        let bridge = (text_h * 0.05) as usize + 1;
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for (x, &inked) in col_inked.iter().enumerate() {
+            if !inked {
+                continue;
+            }
+            match runs.last_mut() {
+                Some(last) if x - last.1 <= bridge => last.1 = x + 1,
+                _ => runs.push((x, x + 1)),
+            }
+        }
+
+        // Runs that are neither digit, point nor minus (specks at the top) are dropped.
+        let mut cells: Vec<Cell> = runs
+            .into_iter()
+            .filter_map(|(x0, x1)| {
+                let inked_rows: Vec<usize> = (y0..y1)
+                    .filter(|&y| (x0..x1).any(|x| mask[y * w + x]))
+                    .collect();
+                let (ry0, ry1) = (inked_rows[0], inked_rows[inked_rows.len() - 1] + 1);
+                let run_h = (ry1 - ry0) as f32;
+                let centre = ((ry0 + ry1) as f32 / 2.0 - y0 as f32) / text_h;
+                let kind = if run_h > SHORT_RUN * text_h {
+                    CellKind::Digit
+                } else if centre > 0.7 {
+                    CellKind::Point
+                } else if (0.35..=0.65).contains(&centre) {
+                    CellKind::Minus
+                } else {
+                    return None;
+                };
+                let strength = self.mean_ink(x0, x1, ry0, ry1);
+                Some(Cell {
+                    x0,
+                    x1,
+                    kind,
+                    strength,
+                })
+            })
+            .collect();
+
+        // Widen narrow digit cells leftwards to the typical digit width.
+        let widest = cells
+            .iter()
+            .filter(|c| c.kind == CellKind::Digit)
+            .map(|c| c.x1 - c.x0)
+            .max()?;
+        let digit_w = if (widest as f32) < NARROW_CELL * DEFAULT_ASPECT * text_h {
+            (DEFAULT_ASPECT * text_h) as usize
+        } else {
+            widest
+        };
+        for c in cells.iter_mut().filter(|c| c.kind == CellKind::Digit) {
+            if ((c.x1 - c.x0) as f32) < NARROW_CELL * digit_w as f32 {
+                c.x0 = c.x1.saturating_sub(digit_w);
+            }
+        }
+
+        Some(Layout { y0, y1, cells })
+    }
+
+    fn mean_ink(&self, x0: usize, x1: usize, y0: usize, y1: usize) -> f32 {
+        let n = ((x1 - x0) * (y1 - y0)).max(1) as f32;
+        let sum: f32 = (y0..y1)
+            .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+            .map(|(x, y)| self.at(x, y))
+            .sum();
+        sum / n
+    }
+
+    /// Mean ink of each segment zone of the cell `[x0, x1) × [y0, y1)`.
+    pub fn sample_zones(&self, x0: usize, x1: usize, y0: usize, y1: usize) -> [f32; 7] {
+        let (cw, ch) = ((x1 - x0) as f32, (y1 - y0) as f32);
+        ZONES.map(|(u0, v0, u1, v1)| {
+            let zx0 = x0 + (u0 * cw) as usize;
+            let zx1 = (x0 + (u1 * cw).ceil() as usize).clamp(zx0 + 1, x1);
+            let zy0 = y0 + (v0 * ch) as usize;
+            let zy1 = (y0 + (v1 * ch).ceil() as usize).clamp(zy0 + 1, y1);
+            self.mean_ink(zx0, zx1, zy0, zy1)
+        })
+    }
+
+    /// Area-averaged ink of `[x0, x1) × [y0, y1)` on a `gw × gh` grid, row-major.
+    pub fn resample(
+        &self,
+        x0: usize,
+        x1: usize,
+        y0: usize,
+        y1: usize,
+        gw: usize,
+        gh: usize,
+    ) -> Vec<f32> {
+        let (cw, ch) = ((x1 - x0) as f32, (y1 - y0) as f32);
+        let span = |start: usize, end: usize, size: f32, i: usize, n: usize| {
+            let a = start + (i as f32 * size / n as f32) as usize;
+            let b = (start + ((i + 1) as f32 * size / n as f32).ceil() as usize).clamp(a + 1, end);
+            (a, b)
+        };
+        (0..gh)
+            .flat_map(|gy| (0..gw).map(move |gx| (gx, gy)))
+            .map(|(gx, gy)| {
+                let (bx0, bx1) = span(x0, x1, cw, gx, gw);
+                let (by0, by1) = span(y0, y1, ch, gy, gh);
+                self.mean_ink(bx0, bx1, by0, by1)
+            })
+            .collect()
+    }
+
+    /// Ink map as dark-on-white PNG (what the decoder actually looked at).
+    pub fn preview(&self) -> String {
+        let bytes: Vec<u8> = self.ink.iter().map(|&v| 255 - (v * 255.0) as u8).collect();
+        encode_png(
+            &bytes,
+            self.width as u32,
+            self.height as u32,
+            image::ExtendedColorType::L8,
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum CellKind {
+    Digit,
+    Point,
+    Minus,
+}
+
+pub(super) struct Cell {
+    pub x0: usize,
+    pub x1: usize,
+    pub kind: CellKind,
+    /// Mean ink of the run's bounding box (confidence of points and minus signs).
+    pub strength: f32,
+}
+
+/// Text band `[y0, y1)` and its cells, left to right.
+pub(super) struct Layout {
+    pub y0: usize,
+    pub y1: usize,
+    pub cells: Vec<Cell>,
+}
+
+impl Layout {
+    /// `[x, y, width, height]` of one cell within the text band.
+    pub fn bbox(&self, cell: &Cell) -> [u32; 4] {
+        [
+            cell.x0 as u32,
+            self.y0 as u32,
+            (cell.x1 - cell.x0) as u32,
+            (self.y1 - self.y0) as u32,
+        ]
+    }
+}
+
+// ── Decoding ──────────────────────────────────────────────────────────────────
+
+enum DigitDecode {
+    /// No segment lit — an unused leading position.
+    Blank,
+    Unknown,
+    Char(char, f64),
+}
+
+fn decode_digit(zones: &[f32; 7], threshold: f32, spread: f32) -> DigitDecode {
+    let mut bits = 0u8;
+    let mut margin = 1.0f32;
+    for (i, &z) in zones.iter().enumerate() {
+        if z > threshold {
+            bits |= 1 << i;
+        }
+        margin = margin.min((z - threshold).abs() / (spread / 2.0));
+    }
+    if bits == 0 {
+        return DigitDecode::Blank;
+    }
+    if let Some(&(_, ch)) = PATTERNS.iter().find(|(p, _)| *p == bits) {
+        return DigitDecode::Char(ch, margin as f64);
+    }
+    // One segment misread: accept the unique nearest pattern at reduced confidence.
+    let near: Vec<char> = PATTERNS
+        .iter()
+        .filter(|(p, _)| (p ^ bits).count_ones() == 1)
+        .map(|&(_, ch)| ch)
+        .collect();
+    match near.as_slice() {
+        [ch] => DigitDecode::Char(*ch, margin as f64 * NEAR_MATCH_PENALTY),
+        [first, rest @ ..] if rest.iter().all(|c| c == first) => {
+            DigitDecode::Char(*first, margin as f64 * NEAR_MATCH_PENALTY)
+        }
+        _ => DigitDecode::Unknown,
+    }
+}
+
+/// Split zone means into lit and unlit levels.
+/// Returns `(threshold, spread between the two level means)`.
+fn split_levels(values: impl Iterator<Item = f32>) -> Option<(f32, f32)> {
+    let mut v: Vec<f32> = values.collect();
+    if v.is_empty() {
+        return None;
+    }
+    v.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Best two-class split of the sorted values (Otsu on a handful of samples).
+    let total: f32 = v.iter().sum();
+    let n = v.len() as f32;
+    let mut best = None;
+    let mut low_sum = 0.0f32;
+    for i in 1..v.len() {
+        low_sum += v[i - 1];
+        let (nl, nh) = (i as f32, n - i as f32);
+        let (ml, mh) = (low_sum / nl, (total - low_sum) / nh);
+        let between = nl * nh * (mh - ml) * (mh - ml);
+        if best.map_or(true, |(b, _, _)| between > b) {
+            best = Some((between, (v[i - 1] + v[i]) / 2.0, mh - ml));
+        }
+    }
+
+    match best {
+        Some((_, threshold, spread)) if spread >= MIN_CONTRAST => Some((threshold, spread)),
+        // Every zone looks alike: all lit (an "8") or nothing to read.
+        _ => {
+            let mean = v.iter().sum::<f32>() / n;
+            (mean > 0.5).then_some((mean / 2.0, mean))
+        }
+    }
+}
+
+// ── Histogram helpers ─────────────────────────────────────────────────────────
+
+/// Value at which the cumulative count exceeds `rank`.
+fn percentile(hist: &[u32; 256], rank: u32) -> u8 {
+    let mut acc = 0u32;
+    for (v, &c) in hist.iter().enumerate() {
+        acc += c;
+        if acc > rank {
+            return v as u8;
+        }
+    }
+    255
+}
+
+/// Otsu threshold: values `<= t` form the lower class.
+fn otsu(hist: &[u32; 256]) -> u8 {
+    let total: f64 = hist.iter().map(|&c| c as f64).sum();
+    let sum_all: f64 = hist
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| i as f64 * c as f64)
+        .sum();
+    let (mut w_low, mut sum_low) = (0.0f64, 0.0f64);
+    let (mut best_t, mut best_var) = (0u8, -1.0f64);
+    for (t, &c) in hist.iter().enumerate().take(255) {
+        w_low += c as f64;
+        sum_low += t as f64 * c as f64;
+        let w_high = total - w_low;
+        if w_low == 0.0 || w_high == 0.0 {
+            continue;
+        }
+        let diff = sum_low / w_low - (sum_all - sum_low) / w_high;
+        let var = w_low * w_high * diff * diff;
+        if var > best_var {
+            best_var = var;
+            best_t = t as u8;
+        }
+    }
+    best_t
+}
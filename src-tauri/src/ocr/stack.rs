@@ -0,0 +1,271 @@
+use image::{DynamicImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::crop_region;
+
+// ── Multi-frame compositing ───────────────────────────────────────────────────
+//
+// Glossy display covers throw specular highlights that move from frame to
+// frame while the digits stay put.  Crops of one region from neighbouring
+// frames are aligned to the sampled frame's crop by a small translation
+// (camera shake) and combined per pixel; an operator that discards the
+// brightest values removes the highlight.
+//
+// The same window serves two more cases:
+//   • Multiplexed LED displays light only some digits per frame (the rolling
+//     shutter beats against the scan); the per-pixel maximum shows them all.
+//   • Displays only 8–10 px tall: each neighbour's sub-pixel offset is
+//     estimated, and all frames are resampled onto a finer grid at their
+//     offsets and averaged (shift-and-add super-resolution).
+
+/// Largest shift, in pixels, searched when aligning a neighbouring crop.
+const MAX_SHIFT: u32 = 4;
+/// Largest `super_resolution` magnification; beyond it the output only grows.
+const MAX_SCALE: u32 = 8;
+
+fn default_frames() -> u32 {
+    5
+}
+
+fn default_scale() -> u32 {
+    3
+}
+
+/// Per-region multi-frame setting, e.g. `{"frames": 5, "operator": "median"}`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FrameStack {
+    /// Frames combined, including the sampled one, centred on it.
+    #[serde(default = "default_frames")]
+    pub frames: u32,
+    #[serde(default)]
+    pub operator: StackOperator,
+    /// Search for a small shift between frames before combining.  Absent → on,
+    /// except for `max`: multiplexed displays' frames differ by design.
+    #[serde(default)]
+    pub align: Option<bool>,
+    /// Output magnification for `super_resolution`, at most `MAX_SCALE`.
+    #[serde(default = "default_scale")]
+    pub scale: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StackOperator {
+    /// Darkest value per pixel: highlights only ever brighten.  Best for dark
+    /// digits on a light display.
+    Min,
+    /// Median per pixel: also suppresses dark specks.
+    #[default]
+    Median,
+    /// Mean of the values left after dropping the brightest and darkest quarter.
+    TrimmedMean,
+    /// Brightest value per pixel: every segment lit in any frame.
+    Max,
+    /// Sub-pixel aligned average on a `scale`× finer grid.
+    SuperResolution,
+}
+
+impl FrameStack {
+    fn align(&self) -> bool {
+        self.align.unwrap_or(self.operator != StackOperator::Max)
+    }
+
+    /// Frame offsets of the neighbours relative to the sampled frame.
+    pub fn offsets(&self) -> Vec<i64> {
+        let n = self.frames.max(1) as i64;
+        let start = -(n - 1) / 2;
+        (start..start + n).filter(|&o| o != 0).collect()
+    }
+
+    /// Combine `crop` (at `x`, `y` in the sampled frame) with the same region
+    /// in the `neighbours` frames, keyed by offset as in `offsets`.  Missing
+    /// neighbours (video edges, decode failures) are skipped.
+    pub fn composite(
+        &self,
+        crop: DynamicImage,
+        x: u32,
+        y: u32,
+        neighbours: &HashMap<i64, (Vec<u8>, u32, u32)>,
+    ) -> DynamicImage {
+        let reference = crop.to_rgb8();
+        let mut layers = vec![];
+        for offset in self.offsets() {
+            if let Some((bytes, fw, fh)) = neighbours.get(&offset) {
+                layers.extend(aligned_crop(
+                    bytes,
+                    *fw,
+                    *fh,
+                    x,
+                    y,
+                    &reference,
+                    self.align(),
+                ));
+            }
+        }
+        if layers.is_empty() {
+            return crop;
+        }
+        layers.push((reference, (0.0, 0.0)));
+        DynamicImage::ImageRgb8(match self.operator {
+            StackOperator::SuperResolution => {
+                super_resolve(&layers, self.scale.clamp(1, MAX_SCALE))
+            }
+            operator => combine(&layers, operator),
+        })
+    }
+}
+
+/// The crop of `reference`'s size at (`x`, `y`) in another frame, shifted by
+/// up to `MAX_SHIFT` pixels to best match `reference` when `align` is set,
+/// with the remaining sub-pixel offset: `crop(p + offset) ≈ reference(p)`.
+fn aligned_crop(
+    frame: &[u8],
+    fw: u32,
+    fh: u32,
+    x: u32,
+    y: u32,
+    reference: &RgbImage,
+    align: bool,
+) -> Option<(RgbImage, (f64, f64))> {
+    let (w, h) = reference.dimensions();
+    let margin = if align { MAX_SHIFT } else { 0 };
+    let (x0, y0) = (x.saturating_sub(margin), y.saturating_sub(margin));
+    let area = crop_region(
+        frame,
+        fw,
+        fh,
+        x0,
+        y0,
+        w + (x - x0) + margin,
+        h + (y - y0) + margin,
+    )?
+    .to_rgb8();
+    let (aw, ah) = area.dimensions();
+    if aw < w || ah < h {
+        return None;
+    }
+
+    // Window origins inside `area`; (x - x0, y - y0) is the unshifted one.
+    let (ox, oy) = (x - x0, y - y0);
+    let (xs, ys) = (
+        ox.saturating_sub(margin)..=(ox + margin).min(aw - w),
+        oy.saturating_sub(margin)..=(oy + margin).min(ah - h),
+    );
+    let mut costs: HashMap<(u32, u32), u64> = HashMap::new();
+    for wy in ys.clone() {
+        for wx in xs.clone() {
+            costs.insert((wx, wy), difference(&area, wx, wy, reference));
+        }
+    }
+    let (&(bx, by), _) = costs.iter().min_by_key(|&(&(wx, wy), &c)| (c, wx, wy))?;
+    let cost = |wx: Option<u32>, wy: Option<u32>| costs.get(&(wx?, wy?)).map(|&c| c as f64);
+
+    // Parabola through the costs either side of the best whole-pixel shift.
+    let refine = |before: Option<f64>, at: f64, after: Option<f64>| match (before, after) {
+        (Some(b), Some(a)) if b + a - 2.0 * at > 0.0 => {
+            ((b - a) / (2.0 * (b + a - 2.0 * at))).clamp(-0.5, 0.5)
+        }
+        _ => 0.0,
+    };
+    let at = cost(Some(bx), Some(by)).unwrap_or(0.0);
+    let offset = (
+        refine(
+            cost(bx.checked_sub(1), Some(by)),
+            at,
+            cost(Some(bx + 1), Some(by)),
+        ),
+        refine(
+            cost(Some(bx), by.checked_sub(1)),
+            at,
+            cost(Some(bx), Some(by + 1)),
+        ),
+    );
+    Some((
+        image::imageops::crop_imm(&area, bx, by, w, h).to_image(),
+        offset,
+    ))
+}
+
+/// Sum of absolute green-channel differences between `reference` and the
+/// window of `area` at (`wx`, `wy`), on every second pixel.
+fn difference(area: &RgbImage, wx: u32, wy: u32, reference: &RgbImage) -> u64 {
+    let mut sum = 0u64;
+    for y in (0..reference.height()).step_by(2) {
+        for x in (0..reference.width()).step_by(2) {
+            let a = area.get_pixel(wx + x, wy + y)[1] as i32;
+            let b = reference.get_pixel(x, y)[1] as i32;
+            sum += (a - b).unsigned_abs() as u64;
+        }
+    }
+    sum
+}
+
+/// Per-pixel, per-channel combination of equally sized `layers`.
+fn combine(layers: &[(RgbImage, (f64, f64))], operator: StackOperator) -> RgbImage {
+    let (w, h) = layers[0].0.dimensions();
+    let mut values = Vec::with_capacity(layers.len());
+    RgbImage::from_fn(w, h, |x, y| {
+        let mut out = [0u8; 3];
+        for (c, v) in out.iter_mut().enumerate() {
+            values.clear();
+            values.extend(layers.iter().map(|(l, _)| l.get_pixel(x, y)[c]));
+            values.sort_unstable();
+            *v = match operator {
+                StackOperator::Min => values[0],
+                StackOperator::Max => values[values.len() - 1],
+                StackOperator::Median => values[values.len() / 2],
+                StackOperator::TrimmedMean => {
+                    let cut = values.len() / 4;
+                    let kept = &values[cut..values.len() - cut];
+                    (kept.iter().map(|&v| v as u32).sum::<u32>() / kept.len() as u32) as u8
+                }
+                StackOperator::SuperResolution => {
+                    unreachable!("super-resolution is resolved in `composite`")
+                }
+            };
+        }
+        Rgb(out)
+    })
+}
+
+/// Shift-and-add: sample every layer bilinearly at its sub-pixel offset on a
+/// `scale`× grid and average.
+fn super_resolve(layers: &[(RgbImage, (f64, f64))], scale: u32) -> RgbImage {
+    let (w, h) = layers[0].0.dimensions();
+    let s = scale as f64;
+    RgbImage::from_fn(w * scale, h * scale, |ox, oy| {
+        // Output pixel centre in input coordinates.
+        let (px, py) = ((ox as f64 + 0.5) / s - 0.5, (oy as f64 + 0.5) / s - 0.5);
+        let mut sum = [0.0f64; 3];
+        for (layer, (dx, dy)) in layers {
+            let v = bilinear(layer, px + dx, py + dy);
+            for c in 0..3 {
+                sum[c] += v[c];
+            }
+        }
+        let n = layers.len() as f64;
+        Rgb(sum.map(|v| (v / n).round().clamp(0.0, 255.0) as u8))
+    })
+}
+
+/// Bilinear sample at (`x`, `y`), clamped to the image.
+fn bilinear(img: &RgbImage, x: f64, y: f64) -> [f64; 3] {
+    let (w, h) = (img.width() as f64, img.height() as f64);
+    let (x, y) = (x.clamp(0.0, w - 1.0), y.clamp(0.0, h - 1.0));
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = (
+        (x0 + 1).min(img.width() - 1),
+        (y0 + 1).min(img.height() - 1),
+    );
+    let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+    let mut out = [0.0; 3];
+    for (c, v) in out.iter_mut().enumerate() {
+        let top =
+            img.get_pixel(x0, y0)[c] as f64 * (1.0 - fx) + img.get_pixel(x1, y0)[c] as f64 * fx;
+        let bottom =
+            img.get_pixel(x0, y1)[c] as f64 * (1.0 - fx) + img.get_pixel(x1, y1)[c] as f64 * fx;
+        *v = top * (1.0 - fy) + bottom * fy;
+    }
+    out
+}
@@ -0,0 +1,192 @@
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::sevenseg::{InkMap, Layout};
+use super::{OcrResult, Recognizer, Symbol};
+
+// ── Template glyph classifier ─────────────────────────────────────────────────
+//
+// Learns one display font from user-labeled crops.  Each crop is segmented
+// into character cells with the same ink-map layout the seven-segment decoder
+// uses; every cell becomes a small feature vector (area-averaged ink on a
+// fixed grid plus the cell's aspect ratio).  Recognition is nearest-neighbour
+// over all stored exemplars.
+
+/// Feature grid resolution per cell.
+const GRID_W: usize = 8;
+const GRID_H: usize = 12;
+/// Weight of the width/height feature relative to one grid cell (separates
+/// '.' and '1' from wider glyphs after resampling).
+const ASPECT_WEIGHT: f32 = 4.0;
+
+/// Model file inside the glyph directory.
+pub const MODEL_FILE: &str = "model.json";
+
+/// One training glyph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exemplar {
+    pub label: char,
+    pub features: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GlyphModel {
+    pub exemplars: Vec<Exemplar>,
+}
+
+impl GlyphModel {
+    /// Load `model.json` from a glyph directory.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(MODEL_FILE);
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+        serde_json::from_str(&text).map_err(|e| format!("Parse error in {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(MODEL_FILE);
+        let text = serde_json::to_string(self).map_err(|e| format!("Serialise error: {e}"))?;
+        fs::write(&path, text).map_err(|e| format!("Cannot write {}: {e}", path.display()))
+    }
+
+    /// Add the glyphs of one labeled crop.  Whitespace in `label` is ignored.
+    /// Fails when the crop does not segment into exactly one cell per character.
+    pub fn learn(&mut self, crop: &DynamicImage, label: &str) -> Result<(), String> {
+        let chars: Vec<char> = label.chars().filter(|c| !c.is_whitespace()).collect();
+        let cells = InkMap::from_image(crop)
+            .and_then(|ink| segment(&ink, &ink.layout()?))
+            .ok_or("no characters found")?;
+        if cells.len() != chars.len() {
+            return Err(format!(
+                "found {} characters, label has {}",
+                cells.len(),
+                chars.len()
+            ));
+        }
+        self.exemplars.extend(
+            chars
+                .into_iter()
+                .zip(cells)
+                .map(|(label, features)| Exemplar { label, features }),
+        );
+        Ok(())
+    }
+
+    /// Classify one feature vector, skipping exemplar `skip` (leave-one-out).
+    /// Confidence compares the nearest exemplar with the nearest of any other
+    /// glyph: 1 = unambiguous, 0 = equally close to two glyphs.
+    fn classify(&self, features: &[f32], skip: Option<usize>) -> Option<(char, f64)> {
+        let mut nearest: BTreeMap<char, f32> = BTreeMap::new();
+        for (i, ex) in self.exemplars.iter().enumerate() {
+            if Some(i) == skip {
+                continue;
+            }
+            let d = distance(&ex.features, features);
+            let e = nearest.entry(ex.label).or_insert(f32::INFINITY);
+            *e = e.min(d);
+        }
+        let mut ranked: Vec<(char, f32)> = nearest.into_iter().collect();
+        ranked.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        let (label, d1) = *ranked.first()?;
+        let confidence = match ranked.get(1) {
+            Some(&(_, d2)) => ((d2 - d1) / (d2 + d1).max(f32::EPSILON)) as f64,
+            None => 1.0,
+        };
+        Some((label, confidence))
+    }
+
+    /// Leave-one-out accuracy of every glyph the model knows.
+    pub fn accuracy(&self) -> Vec<GlyphAccuracy> {
+        let mut stats: BTreeMap<char, (usize, usize)> = BTreeMap::new();
+        for (i, ex) in self.exemplars.iter().enumerate() {
+            let correct = self
+                .classify(&ex.features, Some(i))
+                .is_some_and(|(label, _)| label == ex.label);
+            let s = stats.entry(ex.label).or_default();
+            s.0 += 1;
+            s.1 += correct as usize;
+        }
+        stats
+            .into_iter()
+            .map(|(glyph, (exemplars, correct))| GlyphAccuracy {
+                glyph: glyph.to_string(),
+                exemplars,
+                correct,
+                accuracy: correct as f64 / exemplars as f64,
+            })
+            .collect()
+    }
+}
+
+/// Per-glyph leave-one-out result.
+#[derive(Debug, Clone, Serialize)]
+pub struct GlyphAccuracy {
+    pub glyph: String,
+    pub exemplars: usize,
+    pub correct: usize,
+    pub accuracy: f64,
+}
+
+/// Compute the features of each character cell of `layout`.
+fn segment(ink: &InkMap, layout: &Layout) -> Option<Vec<Vec<f32>>> {
+    let text_h = (layout.y1 - layout.y0) as f32;
+    let cells = layout
+        .cells
+        .iter()
+        .map(|c| {
+            let mut f = ink.resample(c.x0, c.x1, layout.y0, layout.y1, GRID_W, GRID_H);
+            f.push((c.x1 - c.x0) as f32 / text_h * ASPECT_WEIGHT);
+            f
+        })
+        .collect::<Vec<_>>();
+    (!cells.is_empty()).then_some(cells)
+}
+
+fn distance(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+// ── Recognizer impl ───────────────────────────────────────────────────────────
+
+pub struct TemplateRecognizer {
+    pub model: Arc<GlyphModel>,
+}
+
+impl Recognizer for TemplateRecognizer {
+    fn name(&self) -> &str {
+        "template"
+    }
+
+    fn recognize(&self, crop: &DynamicImage) -> Option<OcrResult> {
+        let ink = InkMap::from_image(crop)?;
+        let layout = ink.layout()?;
+        let cells = segment(&ink, &layout)?;
+
+        let mut text = String::new();
+        let mut symbols = Vec::with_capacity(cells.len());
+        for (features, cell) in cells.iter().zip(&layout.cells) {
+            let (ch, confidence) = self.model.classify(features, None)?;
+            text.push(ch);
+            symbols.push(Symbol {
+                text: ch.to_string(),
+                confidence,
+                bbox: Some(layout.bbox(cell)),
+            });
+        }
+        let confidence = symbols.iter().map(|s| s.confidence).fold(1.0f64, f64::min);
+
+        eprintln!("[template] result: {text:?} conf={confidence:.3}");
+
+        Some(OcrResult {
+            text,
+            confidence,
+            preview_b64: ink.preview(),
+            engine_name: self.name().to_string(),
+            symbols,
+        })
+    }
+}
@@ -0,0 +1,200 @@
+// ── Unit parsing and conversion ───────────────────────────────────────────────
+//
+// Instruments print a unit suffix next to the number ("12.5 mV", "37.2°C",
+// "1.013bar").  This module recognises such suffixes — optionally with an SI
+// prefix — and converts values between units of the same quantity so that a
+// region toggling between mV and V yields comparable numbers.
+
+/// A base unit known to the parser.
+///
+/// Conversion to the quantity's canonical unit is `value × scale + offset`
+/// (the offset is only non-zero for temperature scales).
+pub struct Unit {
+    pub symbol: &'static str,
+    pub quantity: &'static str,
+    scale: f64,
+    offset: f64,
+    /// Whether SI prefixes may be combined with this unit ("mV", "kPa").
+    prefixable: bool,
+}
+
+const fn unit(symbol: &'static str, quantity: &'static str, scale: f64, prefixable: bool) -> Unit {
+    Unit {
+        symbol,
+        quantity,
+        scale,
+        offset: 0.0,
+        prefixable,
+    }
+}
+
+const UNITS: &[Unit] = &[
+    unit("V", "voltage", 1.0, true),
+    unit("A", "current", 1.0, true),
+    unit("W", "power", 1.0, true),
+    unit("VA", "apparent_power", 1.0, true),
+    unit("Ω", "resistance", 1.0, true),
+    unit("ohm", "resistance", 1.0, true),
+    unit("Hz", "frequency", 1.0, true),
+    unit("rpm", "frequency", 1.0 / 60.0, false),
+    unit("F", "capacitance", 1.0, true),
+    unit("H", "inductance", 1.0, true),
+    unit("s", "time", 1.0, true),
+    unit("min", "time", 60.0, false),
+    unit("h", "time", 3600.0, false),
+    unit("Pa", "pressure", 1.0, true),
+    unit("bar", "pressure", 1e5, true),
+    unit("psi", "pressure", 6894.757, false),
+    unit("mmHg", "pressure", 133.322, false),
+    unit("m", "length", 1.0, true),
+    unit("g", "mass", 1e-3, true),
+    unit("l", "volume", 1e-3, true),
+    unit("L", "volume", 1e-3, true),
+    unit("%", "ratio", 0.01, false),
+    unit("dB", "level", 1.0, false),
+    unit("°C", "temperature", 1.0, false),
+    unit("℃", "temperature", 1.0, false),
+    unit("C", "temperature", 1.0, false),
+    Unit {
+        symbol: "°F",
+        quantity: "temperature",
+        scale: 5.0 / 9.0,
+        offset: -32.0 * 5.0 / 9.0,
+        prefixable: false,
+    },
+    Unit {
+        symbol: "K",
+        quantity: "temperature",
+        scale: 1.0,
+        offset: -273.15,
+        prefixable: false,
+    },
+];
+
+/// SI prefixes accepted in front of prefixable units.
+/// `u` stands in for `µ` because OCR engines rarely produce the Greek letter.
+const PREFIXES: &[(&str, f64)] = &[
+    ("p", 1e-12),
+    ("n", 1e-9),
+    ("µ", 1e-6),
+    ("μ", 1e-6),
+    ("u", 1e-6),
+    ("m", 1e-3),
+    ("c", 1e-2),
+    ("k", 1e3),
+    ("M", 1e6),
+    ("G", 1e9),
+];
+
+/// A unit string resolved to a base unit plus SI prefix factor.
+pub struct ParsedUnit {
+    pub unit: &'static Unit,
+    pub prefix: f64,
+}
+
+impl ParsedUnit {
+    /// Convert `value` expressed in this unit into the quantity's canonical unit.
+    fn to_canonical(&self, value: f64) -> f64 {
+        value * self.prefix * self.unit.scale + self.unit.offset
+    }
+
+    /// Convert a canonical-unit value back into this unit.
+    fn from_canonical(&self, value: f64) -> f64 {
+        (value - self.unit.offset) / (self.unit.scale * self.prefix)
+    }
+}
+
+/// Resolve a unit string such as `"mV"`, `"kPa"` or `"°C"`.
+///
+/// Exact base-unit matches win over prefix + unit splits, so `"mmHg"` and
+/// `"min"` are not read as milli-mHg / milli-in.  If no case-sensitive match
+/// exists the base unit is retried case-insensitively (OCR often confuses
+/// `hz`/`Hz`); the prefix is always matched case-sensitively since `m` ≠ `M`.
+/// Single-character symbols are never retried: a lone `k` is far more likely
+/// a mangled prefix than Kelvin.
+pub fn parse_unit(s: &str) -> Option<ParsedUnit> {
+    let s: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+    if s.is_empty() {
+        return None;
+    }
+    resolve_unit(&s, |sym, u| u.symbol == sym).or_else(|| {
+        resolve_unit(&s, |sym, u| {
+            u.symbol.chars().count() > 1 && u.symbol.eq_ignore_ascii_case(sym)
+        })
+    })
+}
+
+/// Match `s` as a bare unit first, then as SI prefix + prefixable unit.
+fn resolve_unit(s: &str, matches: impl Fn(&str, &Unit) -> bool) -> Option<ParsedUnit> {
+    if let Some(unit) = UNITS.iter().find(|u| matches(s, *u)) {
+        return Some(ParsedUnit { unit, prefix: 1.0 });
+    }
+    PREFIXES.iter().find_map(|&(p, prefix)| {
+        let rest = s.strip_prefix(p)?;
+        let unit = UNITS.iter().find(|u| u.prefixable && matches(rest, *u))?;
+        Some(ParsedUnit { unit, prefix })
+    })
+}
+
+/// Every character that can appear in a unit symbol of `quantity`,
+/// including SI prefixes when one of its units takes them.
+pub fn symbol_chars(quantity: &str) -> Vec<char> {
+    let units: Vec<&Unit> = UNITS.iter().filter(|u| u.quantity == quantity).collect();
+    let mut out: Vec<char> = units.iter().flat_map(|u| u.symbol.chars()).collect();
+    if units.iter().any(|u| u.prefixable) {
+        out.extend(PREFIXES.iter().flat_map(|(p, _)| p.chars()));
+    }
+    out
+}
+
+/// Convert `value` from unit `from` into unit `to`.
+/// Returns `None` when the two units measure different quantities.
+pub fn convert(value: f64, from: &ParsedUnit, to: &ParsedUnit) -> Option<f64> {
+    if from.unit.quantity != to.unit.quantity {
+        return None;
+    }
+    Some(to.from_canonical(from.to_canonical(value)))
+}
+
+/// Extract the unit suffix that follows the first number in raw OCR text.
+///
+/// `"12.5 mV DC"` → `"mV"`, `"37.2°C"` → `"°C"`, `"1234"` → `""`.
+/// `confusable` marks letters that the number token may contain before
+/// clean-up (`"1O.5V"` → `"V"`, not `"O"`).  Only characters that can appear
+/// in a unit symbol are kept; a space between a degree sign and its scale
+/// letter (`"37 ° C"`) is tolerated.  With `exponent`, an exponent after the
+/// number (`"1.5e-3 V"`) is skipped rather than read as a unit.
+pub fn unit_suffix(text: &str, confusable: impl Fn(char) -> bool, exponent: bool) -> String {
+    let line = text.lines().next().unwrap_or("").trim();
+    let Some(start) = line.find(|c: char| c.is_ascii_digit()) else {
+        return String::new();
+    };
+    let end = line[start..]
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | ',' | '\'') || confusable(c)))
+        .map_or(line.len(), |i| start + i);
+    let mut rest = &line[end..];
+    // Skip an exponent ("1.5e-3 V") so it is not mistaken for a unit.
+    if let Some(exp) = rest.strip_prefix(['e', 'E']).filter(|_| exponent) {
+        let digits = exp.trim_start_matches(['+', '-']);
+        if digits.starts_with(|c: char| c.is_ascii_digit()) {
+            rest = digits.trim_start_matches(|c: char| c.is_ascii_digit());
+        }
+    }
+    let rest = rest.trim_start();
+
+    let mut out = String::new();
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_alphabetic() || matches!(c, '°' | '℃' | 'Ω' | 'µ' | 'μ' | '%') {
+            out.push(c);
+            if c == '°' {
+                while chars.peek().is_some_and(|n| n.is_whitespace()) {
+                    chars.next();
+                }
+            }
+        } else {
+            break;
+        }
+    }
+    out
+}
@@ -1,16 +1,28 @@
-use crate::config::RegionConfig;
+use crate::config::{RegionConfig, RegionExpectation, RegionSettings, Resolution};
+use crate::derived::{format_value, DerivedChannels, Sample};
 use crate::ocr::{
-    oar::{build_pipeline, ColorMode, OarRecognizer},
-    read_region,
-    tesseract::{Preprocess, TesseractRecognizer},
-    Recognizer,
+    allowed_chars,
+    calibration::{CalibratedRecognizer, Calibration},
+    crop_region, focus_crop, interpret_text,
+    oar::{build_pipeline, ColorMode, OarPipeline, OarRecognizer},
+    plugin::{PluginHost, PluginRecognizer, PluginStage},
+    preprocess::{deskew, Deskew, Pipeline, PipelineSpec, Preprocess, Skew},
+    read_region, recognize_all,
+    sevenseg::SevenSegRecognizer,
+    stack::FrameStack,
+    template::{GlyphModel, TemplateRecognizer, MODEL_FILE},
+    tesseract::{ApiPool, TesseractRecognizer},
+    BoxPick, OcrResult, Recognizer, RegionReading, Selection, Symbol,
 };
+use crate::video::RgbFrame;
+use image::DynamicImage;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 use tauri::{AppHandle, Emitter};
 
@@ -32,7 +44,7 @@ fn default_oar_threshold() -> f64 {
     0.9
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ExtractParams {
     pub video_path: String,
     pub config: RegionConfig,
@@ -43,6 +55,19 @@ pub struct ExtractParams {
     /// above this value the Tesseract engines are skipped.  Defaults to 0.9.
     #[serde(default = "default_oar_threshold")]
     pub oar_confidence_threshold: f64,
+    /// Glyph directory of the project (see `project::retrain_glyphs`); its trained
+    /// model backs the "template" engine.
+    #[serde(default)]
+    pub glyph_dir: Option<String>,
+    /// How the final reading is chosen among engine candidates.  Defaults to `best`.
+    #[serde(default)]
+    pub selection: Selection,
+    /// Per-engine confidence calibration (see `project::calibrate_project`).
+    #[serde(default)]
+    pub calibration: Option<Calibration>,
+    /// Keep each reading's per-symbol detail in the measurements and the CSV.
+    #[serde(default)]
+    pub export_symbols: bool,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -54,6 +79,18 @@ pub struct Measurement {
     pub confidence: f64,
     pub raw_text: String,
     pub source: String,
+    /// Unit suffix as printed on the display (e.g. "mV"); empty when absent.
+    pub unit: String,
+    /// `value` converted into the region's target unit (numeric regions only).
+    pub normalized_value: Option<f64>,
+    /// Character-confusion substitutions applied to the reading (e.g. "O→0").
+    pub substitutions: Vec<String>,
+    /// Decimal point / minus sign restored from the crop (e.g. "1234→12.34").
+    pub punctuation: Option<String>,
+    /// Per-symbol detail of `raw_text`; only kept with `ExtractParams::export_symbols`.
+    pub symbols: Vec<Symbol>,
+    /// Rotation and shear the crop was corrected by, when deskewing applied one.
+    pub skew: Option<Skew>,
 }
 
 /// Per-region result emitted inside each frame progress event.
@@ -64,6 +101,9 @@ pub struct RegionProgress {
     pub confidence: f64,
     pub ocr_preview: String,
     pub source: String,
+    /// Per-symbol text, confidence and box of the reading.
+    pub symbols: Vec<Symbol>,
+    pub skew: Option<Skew>,
 }
 
 /// One event emitted per frame (contains all regions, not one per region).
@@ -80,6 +120,8 @@ pub struct ExtractProgress {
 pub struct ExtractResult {
     pub measurements: Vec<Measurement>,
     pub csv: String,
+    /// Non-fatal problems worth showing to the user (e.g. aspect-ratio mismatch).
+    pub warnings: Vec<String>,
 }
 
 #[tauri::command]
@@ -87,6 +129,425 @@ pub async fn extract(
     app: AppHandle,
     params: ExtractParams,
     cancel: tauri::State<'_, CancelFlag>,
+) -> Result<ExtractResult, String> {
+    let engines = EngineContext::locate(&app);
+    let flag = cancel.0.clone();
+    flag.store(false, Ordering::Relaxed);
+    run_extraction(params, &engines, &flag, &|progress| {
+        let _ = app.emit("extraction_progress", progress);
+    })
+}
+
+// ── OCR engines ───────────────────────────────────────────────────────────────
+
+/// OCR resources located once and shared by every engine list built from them
+/// (one extraction, or all videos of a project).
+pub struct EngineContext {
+    oar: Option<Arc<OarPipeline>>,
+    tessdata_dir: Option<String>,
+    /// Initialised Tesseract instances; freed when the context is dropped at
+    /// the end of the command.
+    tess_pool: Arc<ApiPool>,
+    /// Plugin processes by their whole spec (videos of a project may configure
+    /// one name differently), started on first use and stopped when the
+    /// context is dropped.
+    plugins: Mutex<HashMap<String, Arc<PluginHost>>>,
+}
+
+impl EngineContext {
+    pub fn locate(app: &AppHandle) -> Self {
+        use tauri::Manager as _;
+
+        // Locate bundled model files.
+        // Dev: src-tauri/models/ (baked in via CARGO_MANIFEST_DIR).
+        // Prod: Tauri resource directory.
+        let oar = {
+            let candidates = [
+                std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("models"),
+                app.path()
+                    .resource_dir()
+                    .map(|d| d.join("models"))
+                    .unwrap_or_default(),
+            ];
+
+            let found = candidates.iter().find(|d| {
+                d.join("pp-ocrv5_mobile_rec.onnx").exists() && d.join("ppocrv5_dict.txt").exists()
+            });
+
+            if let Some(dir) = found {
+                let rec = dir.join("pp-ocrv5_mobile_rec.onnx");
+                let dict = dir.join("ppocrv5_dict.txt");
+                let det = dir.join("pp-ocrv5_mobile_det.onnx");
+                match build_pipeline(
+                    rec.to_str().unwrap_or(""),
+                    dict.to_str().unwrap_or(""),
+                    det.exists().then(|| det.to_str().unwrap_or("")),
+                ) {
+                    Ok(pipeline) => {
+                        eprintln!("oar-ocr pipeline ready (models: {dir:?})");
+                        Some(Arc::new(pipeline))
+                    }
+                    Err(e) => {
+                        eprintln!("oar-ocr init failed: {e}");
+                        None
+                    }
+                }
+            } else {
+                eprintln!("oar-ocr models not found — Tesseract only");
+                None
+            }
+        };
+
+        // Locate bundled Tesseract tessdata.
+        // kreuzberg-tesseract's init(datadir, lang) treats datadir as the directory
+        // that directly contains <lang>.traineddata files (not the parent of tessdata/).
+        // Dev:  src-tauri/tessdata/ downloaded by build.rs.
+        // Prod: Tauri resource_dir()/tessdata/ bundled via tauri.conf.json.
+        let tessdata_dir: Option<String> = {
+            let candidates = [
+                std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+                app.path().resource_dir().unwrap_or_default(),
+            ];
+            candidates
+                .iter()
+                .find(|d| d.join("tessdata").join("eng.traineddata").exists())
+                .map(|d| d.join("tessdata").to_string_lossy().to_string())
+        };
+        if let Some(ref td) = tessdata_dir {
+            eprintln!("tesseract tessdata found at {td:?}");
+        } else {
+            eprintln!("tesseract tessdata not found — falling back to system default");
+        }
+
+        EngineContext {
+            oar,
+            tessdata_dir,
+            tess_pool: Arc::default(),
+            plugins: Mutex::default(),
+        }
+    }
+
+    /// The oar-ocr pipeline, if its models were found.
+    pub fn oar(&self) -> Option<&Arc<OarPipeline>> {
+        self.oar.as_ref()
+    }
+
+    /// Build the engines for one region.
+    ///
+    /// `priority` engines (oar-ocr variants) run first on every region.
+    ///   → If the best result exceeds the fast-path threshold the
+    ///     `fallback` engines (Tesseract variants) are skipped entirely.
+    ///
+    /// `fallback` engines only run when oar-ocr is not confident enough.
+    ///
+    /// For `region`, its `RegionConfig::region_settings` entry may narrow the
+    /// engines and replace the Tesseract variants, threshold and languages, and
+    /// a numeric expectation restricts the characters engines may output.
+    /// `None` builds the extraction-wide default set.
+    ///
+    /// To add a new OCR backend: implement `Recognizer` in `src/ocr/<backend>.rs`
+    /// and push a `Box<dyn Recognizer>` into one of the two lists here.
+    /// Executables outside the app plug in through `RegionConfig::plugins`
+    /// (see `ocr::plugin`).
+    pub fn engine_set(
+        &self,
+        params: &ExtractParams,
+        region: Option<&str>,
+        glyphs: Option<&Arc<GlyphModel>>,
+    ) -> Result<EngineSet, String> {
+        let settings: RegionSettings = region
+            .and_then(|r| params.config.region_settings.get(r))
+            .cloned()
+            .unwrap_or_default();
+        let languages = settings.languages.as_ref().unwrap_or(&params.languages);
+        let charset = region
+            .and_then(|r| params.config.expectations.get(r))
+            .and_then(allowed_chars);
+
+        // Priority: oar-ocr (RGB input + grayscale input, or the region's pipelines)
+        let oar_charset = || charset.as_ref().map(|c| c.chars().collect());
+        let mut priority: Vec<Box<dyn Recognizer>> = match (&self.oar, &settings.oar_preprocess) {
+            (Some(pipeline), Some(specs)) => specs
+                .iter()
+                .map(|spec| {
+                    Box::new(OarRecognizer::new(
+                        pipeline.clone(),
+                        ColorMode::Rgb,
+                        oar_charset(),
+                        Some(spec.resolve()),
+                    )) as Box<dyn Recognizer>
+                })
+                .collect(),
+            (Some(pipeline), None) => vec![
+                Box::new(OarRecognizer::new(
+                    pipeline.clone(),
+                    ColorMode::Rgb,
+                    oar_charset(),
+                    None,
+                )) as Box<dyn Recognizer>,
+                Box::new(OarRecognizer::new(
+                    pipeline.clone(),
+                    ColorMode::Grayscale,
+                    oar_charset(),
+                    None,
+                )),
+            ],
+            (None, _) => vec![],
+        };
+
+        // Fallback: Tesseract variants (run when oar-ocr confidence is below threshold).
+        //
+        // When preprocessing is enabled:
+        //   • Binary  — full pipeline with Sauvola binarization + morph opening
+        //   • Gray    — same pipeline, stops at enhanced grayscale (no binarization)
+        //   • ChannelR/G/B — extract each colour channel independently, then Binary pipeline
+        //                    (helps with coloured digit displays: red LEDs, green LCDs, etc.)
+        // When preprocessing is disabled: RawGray (no upscaling, minimal cost).
+        // RawRgb is always included as a final Tesseract fallback.
+        // A region's `preprocess` list replaces these with presets or custom pipelines.
+        let default_variants: &[Preprocess] = if params.preprocess {
+            &[
+                Preprocess::Binary,
+                Preprocess::Gray,
+                Preprocess::ChannelR,
+                Preprocess::ChannelG,
+                Preprocess::ChannelB,
+                Preprocess::RawRgb,
+            ]
+        } else {
+            &[Preprocess::RawGray, Preprocess::RawRgb]
+        };
+        let variants: Vec<Pipeline> = match &settings.preprocess {
+            Some(specs) => specs.iter().map(PipelineSpec::resolve).collect(),
+            None => default_variants.iter().map(|p| p.pipeline()).collect(),
+        };
+        let mut fallback: Vec<Box<dyn Recognizer>> = variants
+            .into_iter()
+            .map(|pipeline| {
+                Box::new(TesseractRecognizer::new(
+                    pipeline,
+                    languages.clone(),
+                    self.tessdata_dir.clone(),
+                    charset.clone(),
+                    self.tess_pool.clone(),
+                )) as Box<dyn Recognizer>
+            })
+            .collect();
+
+        // Plugins join the list their spec names, before selection so
+        // "plugin" or "plugin/<name>" can narrow a region to them.
+        let mut hosts = self.plugins.lock().map_err(|_| "plugin table poisoned")?;
+        for (i, spec) in params.config.plugins.iter().enumerate() {
+            if spec.name.is_empty() || spec.name.contains('/') {
+                return Err(format!("Invalid plugin name '{}'", spec.name));
+            }
+            if params.config.plugins[..i]
+                .iter()
+                .any(|p| p.name == spec.name)
+            {
+                return Err(format!("Duplicate plugin name '{}'", spec.name));
+            }
+            let key = serde_json::to_string(spec).map_err(|e| e.to_string())?;
+            let host = hosts
+                .entry(key)
+                .or_insert_with(|| Arc::new(PluginHost::new(spec.clone())))
+                .clone();
+            let plugin = PluginRecognizer::new(host, charset.clone());
+            match plugin.stage() {
+                PluginStage::Priority => priority.push(Box::new(plugin)),
+                PluginStage::Fallback => fallback.push(Box::new(plugin)),
+            }
+        }
+        drop(hosts);
+
+        // Engine selection: keep engines whose name equals a selector or
+        // lies under it ("tesseract" selects "tesseract/channel-r").
+        if let Some(selectors) = &settings.engines {
+            for sel in selectors {
+                if !ENGINE_FAMILIES
+                    .iter()
+                    .any(|f| sel == f || sel.strip_prefix(f).is_some_and(|r| r.starts_with('/')))
+                {
+                    return Err(format!("Unknown OCR engine '{sel}'"));
+                }
+            }
+            let selected = |e: &Box<dyn Recognizer>| {
+                let name = e.name();
+                selectors.iter().any(|sel| {
+                    name == sel
+                        || name
+                            .strip_prefix(sel.as_str())
+                            .is_some_and(|r| r.starts_with('/'))
+                })
+            };
+            priority.retain(selected);
+            fallback.retain(selected);
+
+            // Opt-in engines: only run for regions that name them.
+            // Seven-segment decoding is cheap and deterministic, so it runs first.
+            if selectors.iter().any(|s| s == "sevenseg") {
+                priority.insert(0, Box::new(SevenSegRecognizer));
+            }
+            if selectors.iter().any(|s| s == "template") {
+                let model = glyphs.ok_or("engine 'template' needs a trained glyph model")?;
+                priority.insert(
+                    0,
+                    Box::new(TemplateRecognizer {
+                        model: model.clone(),
+                    }),
+                );
+            }
+        }
+
+        let mut warnings = Vec::new();
+        let detect = match (settings.detect, &self.oar) {
+            (Some(pick), Some(pipeline)) if pipeline.has_detector() => {
+                Some((pipeline.clone(), pick))
+            }
+            (Some(_), _) => {
+                let w = format!(
+                    "Region '{}': text detection unavailable — reading the whole region",
+                    region.unwrap_or_default()
+                );
+                eprintln!("warning: {w}");
+                warnings.push(w);
+                None
+            }
+            (None, _) => None,
+        };
+
+        // Calibrated engines report comparable confidences (see `ocr::calibration`).
+        if let Some(cal) = &params.calibration {
+            priority = calibrate(priority, cal);
+            fallback = calibrate(fallback, cal);
+        }
+
+        Ok(EngineSet {
+            priority,
+            fallback,
+            // Clamp threshold to [0, 1] — invalid values from the frontend become safe defaults.
+            fast_threshold: settings
+                .fast_threshold
+                .unwrap_or(params.oar_confidence_threshold)
+                .clamp(0.0, 1.0),
+            selection: settings.selection.unwrap_or(params.selection),
+            detect,
+            warnings,
+            deskew: settings.deskew,
+            stack: settings.stack,
+        })
+    }
+}
+
+/// Wrap every engine that has a fitted mapping in `cal`.
+fn calibrate(engines: Vec<Box<dyn Recognizer>>, cal: &Calibration) -> Vec<Box<dyn Recognizer>> {
+    engines
+        .into_iter()
+        .map(|inner| match cal.engines.get(inner.name()).cloned() {
+            Some(calibration) => {
+                Box::new(CalibratedRecognizer { inner, calibration }) as Box<dyn Recognizer>
+            }
+            None => inner,
+        })
+        .collect()
+}
+
+/// Load the trained glyph model from a project's glyph directory, if one exists.
+pub fn load_glyphs(glyph_dir: Option<&str>) -> Result<Option<Arc<GlyphModel>>, String> {
+    match glyph_dir {
+        Some(dir) if Path::new(dir).join(MODEL_FILE).exists() => {
+            Ok(Some(Arc::new(GlyphModel::load(Path::new(dir))?)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Engine families accepted as selectors in `RegionSettings::engines`.
+const ENGINE_FAMILIES: &[&str] = &["oar-ocr", "tesseract", "sevenseg", "template", "plugin"];
+
+/// The engines, fast-path threshold and winner selection used for one region.
+pub struct EngineSet {
+    pub priority: Vec<Box<dyn Recognizer>>,
+    pub fallback: Vec<Box<dyn Recognizer>>,
+    pub fast_threshold: f64,
+    pub selection: Selection,
+    /// Text detection narrowing the crop before recognition.
+    pub detect: Option<(Arc<OarPipeline>, BoxPick)>,
+    /// Rotation/shear correction applied to the crop after detection.
+    pub deskew: Option<Deskew>,
+    /// Neighbouring frames composited into the crop before anything else.
+    pub stack: Option<FrameStack>,
+    /// Configured features that could not be set up, for `ExtractResult::warnings`.
+    pub warnings: Vec<String>,
+}
+
+/// A region crop ready for recognition.
+pub struct PreparedCrop {
+    pub image: DynamicImage,
+    /// The priority engines' results on `image`, when choosing the text box
+    /// already produced them.
+    pub priority: Option<Vec<OcrResult>>,
+    /// Correction applied by deskewing; `None` when the crop was left as cut.
+    pub skew: Option<Skew>,
+}
+
+impl EngineSet {
+    pub fn is_empty(&self) -> bool {
+        self.priority.is_empty() && self.fallback.is_empty()
+    }
+
+    /// The crop every engine of this set reads: narrowed to the detected
+    /// text box, then deskewed, as configured.
+    pub fn prepare_crop(
+        &self,
+        crop: DynamicImage,
+        expectation: Option<&RegionExpectation>,
+        prev_value: Option<f64>,
+    ) -> PreparedCrop {
+        let (crop, priority) = match &self.detect {
+            Some((pipeline, pick)) => focus_crop(
+                crop,
+                pipeline,
+                *pick,
+                &self.priority,
+                expectation,
+                prev_value,
+            ),
+            None => (crop, None),
+        };
+        let Some(limits) = self.deskew else {
+            return PreparedCrop {
+                image: crop,
+                priority,
+                skew: None,
+            };
+        };
+        let (image, _, skew) = deskew(&crop, limits);
+        if skew == Skew::default() {
+            return PreparedCrop {
+                image,
+                priority,
+                skew: None,
+            };
+        }
+        // Results on the box no longer describe the corrected image.
+        PreparedCrop {
+            image,
+            priority: None,
+            skew: Some(skew),
+        }
+    }
+}
+
+// ── Extraction ────────────────────────────────────────────────────────────────
+
+/// Run one extraction to completion (or until `cancel` is set).
+/// `on_progress` receives one event per sampled frame.
+pub fn run_extraction(
+    params: ExtractParams,
+    engines: &EngineContext,
+    cancel: &AtomicBool,
+    on_progress: &(dyn Fn(&ExtractProgress) + Sync),
 ) -> Result<ExtractResult, String> {
     use crate::video::get_video_info;
 
@@ -94,6 +555,30 @@ pub async fn extract(
         return Err("At least 2 keyframes are required to run extraction.".to_string());
     }
 
+    // Parse derived-channel formulas and composite templates up front so a typo fails fast.
+    let mut derived = DerivedChannels::compile(&params.config.derived)?;
+
+    // Children of composites are only reported when the composite asks for it.
+    let region_names: HashSet<&str> = params
+        .config
+        .keyframes
+        .iter()
+        .flat_map(|kf| kf.regions.iter().map(|r| r.name.as_str()))
+        .collect();
+    let mut hidden_children: HashSet<String> = HashSet::new();
+    for c in &params.config.composites {
+        let children = c.children()?;
+        if let Some(unknown) = children.iter().find(|n| !region_names.contains(*n)) {
+            return Err(format!(
+                "Composite '{}': no region named '{unknown}'",
+                c.name
+            ));
+        }
+        if !c.emit_children {
+            hidden_children.extend(children.into_iter().map(str::to_string));
+        }
+    }
+
     let mut kf_ts: Vec<f64> = params
         .config
         .keyframes
@@ -106,266 +591,454 @@ pub async fn extract(
 
     let info = get_video_info(params.video_path.clone())?;
     let fps = info.fps;
+
+    // Region coordinates are mapped onto this video if the config was drawn
+    // at another size (see `RegionConfig::get_regions_at`).
+    let frame_size = Resolution {
+        width: info.width,
+        height: info.height,
+    };
+    let mut warnings: Vec<String> = Vec::new();
+    if let Some(w) = params.config.resolution_warning(frame_size) {
+        eprintln!("warning: {w}");
+        warnings.push(w);
+    }
     let fps_sample = params.fps_sample.max(1) as u64;
 
+    // Per-region sample intervals in frames.  The loop steps by their greatest
+    // common divisor and OCRs a region only on frames that are a multiple of its own.
+    let intervals: HashMap<String, u64> = params
+        .config
+        .region_settings
+        .iter()
+        .filter_map(|(name, s)| Some((name.clone(), s.sample_interval?.frames(fps))))
+        .collect();
+    let step = intervals.values().fold(fps_sample, |a, &b| gcd(a, b));
+
     let first_frame = (first_ts * fps).round() as u64;
     let last_frame = (last_ts * fps).round() as u64;
-    let total_steps = (last_frame.saturating_sub(first_frame)) / fps_sample + 1;
-
-    // ── Build OCR engine lists ────────────────────────────────────────────────
-    //
-    // `priority` engines (oar-ocr variants) run first on every region.
-    //   → If the best result exceeds `oar_confidence_threshold` the
-    //     `fallback` engines (Tesseract variants) are skipped entirely.
-    //
-    // `fallback` engines only run when oar-ocr is not confident enough.
-    //
-    // To add a new OCR backend: implement `Recognizer` in `src/ocr/<backend>.rs`
-    // and push a `Box<dyn Recognizer>` into one of the two lists here.
-
-    // Priority: oar-ocr (RGB input + grayscale input)
-    let priority_engines: Vec<Box<dyn Recognizer>> = {
-        // Locate bundled model files.
-        // Dev: src-tauri/models/ (baked in via CARGO_MANIFEST_DIR).
-        // Prod: Tauri resource directory.
-        use tauri::Manager as _;
-        let candidates = [
-            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("models"),
-            app.path()
-                .resource_dir()
-                .map(|d| d.join("models"))
-                .unwrap_or_default(),
-        ];
-
-        let found = candidates.iter().find(|d| {
-            d.join("pp-ocrv5_mobile_rec.onnx").exists() && d.join("ppocrv5_dict.txt").exists()
-        });
+    let total_steps = (last_frame.saturating_sub(first_frame)) / step + 1;
 
-        if let Some(dir) = found {
-            let rec = dir.join("pp-ocrv5_mobile_rec.onnx");
-            let dict = dir.join("ppocrv5_dict.txt");
-            match build_pipeline(rec.to_str().unwrap_or(""), dict.to_str().unwrap_or("")) {
-                Ok(pipeline) => {
-                    eprintln!("oar-ocr pipeline ready (models: {dir:?})");
-                    let pipeline = Arc::new(pipeline);
-                    vec![
-                        Box::new(OarRecognizer {
-                            pipeline: pipeline.clone(),
-                            color_mode: ColorMode::Rgb,
-                        }) as Box<dyn Recognizer>,
-                        Box::new(OarRecognizer {
-                            pipeline,
-                            color_mode: ColorMode::Grayscale,
-                        }),
-                    ]
-                }
-                Err(e) => {
-                    eprintln!("oar-ocr init failed: {e}");
-                    vec![]
-                }
-            }
-        } else {
-            eprintln!("oar-ocr models not found — Tesseract only");
-            vec![]
-        }
-    };
+    // A trained glyph model is loaded once and shared by every region selecting it.
+    let glyphs = load_glyphs(params.glyph_dir.as_deref())?;
 
-    // Locate bundled Tesseract tessdata.
-    // kreuzberg-tesseract's init(datadir, lang) treats datadir as the directory
-    // that directly contains <lang>.traineddata files (not the parent of tessdata/).
-    // Dev:  src-tauri/tessdata/ downloaded by build.rs.
-    // Prod: Tauri resource_dir()/tessdata/ bundled via tauri.conf.json.
-    let tessdata_dir: Option<String> = {
-        use tauri::Manager as _;
-        let candidates = [
-            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")),
-            app.path().resource_dir().unwrap_or_default(),
-        ];
-        candidates
-            .iter()
-            .find(|d| d.join("tessdata").join("eng.traineddata").exists())
-            .map(|d| d.join("tessdata").to_string_lossy().to_string())
-    };
-    if let Some(ref td) = tessdata_dir {
-        eprintln!("tesseract tessdata found at {td:?}");
-    } else {
-        eprintln!("tesseract tessdata not found — falling back to system default");
-    }
-
-    // Fallback: Tesseract variants (run when oar-ocr confidence is below threshold).
-    //
-    // When preprocessing is enabled:
-    //   • Binary  — full pipeline with Sauvola binarization + morph opening
-    //   • Gray    — same pipeline, stops at enhanced grayscale (no binarization)
-    //   • ChannelR/G/B — extract each colour channel independently, then Binary pipeline
-    //                    (helps with coloured digit displays: red LEDs, green LCDs, etc.)
-    // When preprocessing is disabled: RawGray (no upscaling, minimal cost).
-    // RawRgb is always included as a final Tesseract fallback.
-    let fallback_engines: Vec<Box<dyn Recognizer>> = {
-        let mut v: Vec<Box<dyn Recognizer>> = Vec::new();
-        if params.preprocess {
-            v.push(Box::new(TesseractRecognizer {
-                languages: params.languages.clone(),
-                preprocess: Preprocess::Binary,
-                tessdata_dir: tessdata_dir.clone(),
-            }));
-            v.push(Box::new(TesseractRecognizer {
-                languages: params.languages.clone(),
-                preprocess: Preprocess::Gray,
-                tessdata_dir: tessdata_dir.clone(),
-            }));
-            v.push(Box::new(TesseractRecognizer {
-                languages: params.languages.clone(),
-                preprocess: Preprocess::ChannelR,
-                tessdata_dir: tessdata_dir.clone(),
-            }));
-            v.push(Box::new(TesseractRecognizer {
-                languages: params.languages.clone(),
-                preprocess: Preprocess::ChannelG,
-                tessdata_dir: tessdata_dir.clone(),
-            }));
-            v.push(Box::new(TesseractRecognizer {
-                languages: params.languages.clone(),
-                preprocess: Preprocess::ChannelB,
-                tessdata_dir: tessdata_dir.clone(),
-            }));
-        } else {
-            v.push(Box::new(TesseractRecognizer {
-                languages: params.languages.clone(),
-                preprocess: Preprocess::RawGray,
-                tessdata_dir: tessdata_dir.clone(),
-            }));
+    // Regions with settings or a restricted character set get their own engine
+    // set; the rest share the default (index 0).  Regions configured alike share
+    // a set, so their crops are recognized in one batch.
+    let mut engine_sets: Vec<EngineSet> =
+        vec![engines.engine_set(&params, None, glyphs.as_ref())?];
+    let mut region_engines: HashMap<String, usize> = HashMap::new();
+    let mut set_keys: HashMap<(String, Option<String>), usize> = HashMap::new();
+    let own_engines: HashSet<&String> = params
+        .config
+        .region_settings
+        .keys()
+        .chain(
+            params
+                .config
+                .expectations
+                .iter()
+                .filter(|(_, e)| allowed_chars(e).is_some())
+                .map(|(name, _)| name),
+        )
+        .collect();
+    for name in own_engines {
+        let key = (
+            serde_json::to_string(&params.config.region_settings.get(name)).unwrap_or_default(),
+            params.config.expectations.get(name).and_then(allowed_chars),
+        );
+        if let Some(&index) = set_keys.get(&key) {
+            region_engines.insert(name.clone(), index);
+            continue;
         }
-        // Raw RGB is always included as an additional Tesseract variant
-        v.push(Box::new(TesseractRecognizer {
-            languages: params.languages.clone(),
-            preprocess: Preprocess::RawRgb,
-            tessdata_dir,
-        }));
-        v
-    };
+        let set = engines
+            .engine_set(&params, Some(name), glyphs.as_ref())
+            .map_err(|e| format!("Region '{name}': {e}"))?;
+        if set.is_empty() {
+            let w = format!("Region '{name}': none of the selected OCR engines are available");
+            eprintln!("warning: {w}");
+            warnings.push(w);
+        }
+        warnings.extend(set.warnings.iter().cloned());
+        engine_sets.push(set);
+        set_keys.insert(key, engine_sets.len() - 1);
+        region_engines.insert(name.clone(), engine_sets.len() - 1);
+    }
 
     // ── Frame loop ────────────────────────────────────────────────────────────
 
-    let flag = cancel.0.clone();
-    flag.store(false, Ordering::Relaxed);
-
-    // Clamp threshold to [0, 1] — invalid values from the frontend become safe defaults.
-    let oar_threshold = params.oar_confidence_threshold.clamp(0.0, 1.0);
-
     // Track the last accepted numeric reading per region for deviation scoring.
     let mut prev_values: HashMap<String, f64> = HashMap::new();
+    // Latest numeric reading per region for derived channels, held between a
+    // region's samples so slow regions combine with fast ones.
+    let mut latest: HashMap<String, Sample> = HashMap::new();
 
     let mut measurements: Vec<Measurement> = Vec::new();
     let mut elapsed: u64 = 0;
     let mut frame_num = first_frame;
 
     while frame_num <= last_frame {
-        if flag.load(Ordering::Relaxed) {
+        if cancel.load(Ordering::Relaxed) {
             break;
         }
 
         let timestamp = frame_num as f64 / fps;
-        let regions = params.config.get_regions_at(timestamp);
+        let offset = frame_num - first_frame;
+        let mut regions = params.config.get_regions_at(timestamp, frame_size);
+        regions.retain(|r| {
+            let every = intervals.get(&r.name).copied().unwrap_or(fps_sample);
+            offset % every == 0
+        });
 
+        // Only decode when at least one region is due on this frame.
         if regions.is_empty() {
             elapsed += 1;
-            frame_num += fps_sample;
+            frame_num += step;
             continue;
         }
 
-        let (frame_bytes, fw, fh) =
-            match crate::video::decode_frame_at(&params.video_path, timestamp) {
-                Ok(t) => t,
-                Err(e) => {
-                    eprintln!("frame decode failed at {timestamp:.3}s: {e}");
-                    elapsed += 1;
-                    frame_num += fps_sample;
-                    continue;
+        let set_of = |name: &str| region_engines.get(name).copied().unwrap_or(0);
+
+        // Neighbouring frames for regions composited over several frames.  They
+        // and the sampled frame come from one forward decode, so the stack holds
+        // truly adjacent frames.
+        let mut offsets: Vec<i64> = regions
+            .iter()
+            .filter_map(|r| engine_sets[set_of(&r.name)].stack)
+            .flat_map(|s| s.offsets())
+            .filter(|&o| frame_num as i64 + o >= 0)
+            .collect();
+        offsets.sort_unstable();
+        offsets.dedup();
+        let mut neighbours: HashMap<i64, RgbFrame> = HashMap::new();
+        if !offsets.is_empty() {
+            let wanted: Vec<u64> = offsets
+                .iter()
+                .map(|&o| (frame_num as i64 + o) as u64)
+                .chain([frame_num])
+                .collect();
+            match crate::video::decode_frames(&params.video_path, fps, &wanted) {
+                Ok(frames) => {
+                    neighbours = frames
+                        .into_iter()
+                        .map(|(n, f)| (n as i64 - frame_num as i64, f))
+                        .collect();
                 }
-            };
+                Err(e) => eprintln!("neighbour decode failed at {timestamp:.3}s: {e}"),
+            }
+        }
+
+        let decoded = match neighbours.remove(&0) {
+            Some(frame) => Ok(frame),
+            None => crate::video::decode_frame_at(&params.video_path, timestamp),
+        };
+        let (frame_bytes, fw, fh) = match decoded {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("frame decode failed at {timestamp:.3}s: {e}");
+                elapsed += 1;
+                frame_num += step;
+                continue;
+            }
+        };
 
         // Snapshot previous values before parallel processing so all regions in this
         // frame read the *previous* frame's accepted values (not each other's).
         let prev_snap = &prev_values;
 
-        // Run OCR for all regions in parallel, producing (Measurement, RegionProgress) pairs.
-        let outcomes: Vec<(Measurement, RegionProgress)> = regions
+        let mut crops: Vec<Option<PreparedCrop>> = regions
             .par_iter()
             .map(|region| {
-                let expectation = params.config.expectations.get(&region.name);
-                let prev_value = prev_snap.get(&region.name).copied();
-                let (value, confidence, raw_text, ocr_preview, source) = read_region(
+                let (x, y) = (region.x.max(0) as u32, region.y.max(0) as u32);
+                let crop = crop_region(
                     &frame_bytes,
                     fw,
                     fh,
-                    region.x.max(0) as u32,
-                    region.y.max(0) as u32,
+                    x,
+                    y,
                     region.width.max(0) as u32,
                     region.height.max(0) as u32,
-                    &priority_engines,
-                    &fallback_engines,
-                    oar_threshold,
-                    expectation,
-                    prev_value,
-                );
-                (
-                    Measurement {
-                        timestamp,
-                        frame_number: frame_num,
-                        region_name: region.name.clone(),
-                        value: value.clone(),
-                        confidence,
-                        raw_text,
-                        source: source.clone(),
-                    },
-                    RegionProgress {
-                        region_name: region.name.clone(),
-                        value,
-                        confidence,
-                        ocr_preview,
-                        source,
-                    },
-                )
+                )?;
+                let set = &engine_sets[set_of(&region.name)];
+                let crop = match set.stack {
+                    Some(stack) => stack.composite(crop, x, y, &neighbours),
+                    None => crop,
+                };
+                // Loosely drawn regions: read only the detected text box.
+                Some(set.prepare_crop(
+                    crop,
+                    params.config.expectations.get(&region.name),
+                    prev_snap.get(&region.name).copied(),
+                ))
             })
             .collect();
 
-        // Emit one batched event for the entire frame (reduces IPC calls by N_regions).
-        let _ = app.emit(
-            "extraction_progress",
-            ExtractProgress {
-                frame: frame_num,
-                total: total_steps,
-                timestamp,
-                elapsed_frames: elapsed,
-                regions: outcomes.iter().map(|(_, rp)| rp.clone()).collect(),
-            },
-        );
+        // Priority engines see all crops sharing an engine set in one batch
+        // (one inference call per engine instead of one per region).  Crops
+        // whose text box was picked by reading every box already have results.
+        let mut batches: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut priority_results: Vec<Vec<OcrResult>> = vec![Vec::new(); regions.len()];
+        for (i, region) in regions.iter().enumerate() {
+            match crops[i].as_mut().map(|c| c.priority.take()) {
+                Some(Some(results)) => priority_results[i] = results,
+                Some(None) => batches.entry(set_of(&region.name)).or_default().push(i),
+                None => {}
+            }
+        }
+        let batched: Vec<(Vec<usize>, Vec<Vec<OcrResult>>)> = batches
+            .into_par_iter()
+            .map(|(set, members)| {
+                let batch: Vec<DynamicImage> = members
+                    .iter()
+                    .filter_map(|&i| crops[i].as_ref().map(|c| c.image.clone()))
+                    .collect();
+                let results = recognize_all(&engine_sets[set].priority, &batch);
+                (members, results)
+            })
+            .collect();
+        for (members, results) in batched {
+            for (i, r) in members.into_iter().zip(results) {
+                priority_results[i] = r;
+            }
+        }
+
+        // Finish each region in parallel, producing (Measurement, RegionProgress) pairs.
+        let mut outcomes: Vec<(Measurement, RegionProgress)> = regions
+            .par_iter()
+            .zip(crops.par_iter())
+            .zip(priority_results.into_par_iter())
+            .map(|((region, crop), priority_results)| {
+                let reading = match crop {
+                    Some(crop) => {
+                        let set = &engine_sets[set_of(&region.name)];
+                        let reading = read_region(
+                            &crop.image,
+                            priority_results,
+                            &set.fallback,
+                            set.fast_threshold,
+                            set.selection,
+                            params.config.expectations.get(&region.name),
+                            prev_snap.get(&region.name).copied(),
+                        );
+                        RegionReading {
+                            skew: crop.skew,
+                            ..reading
+                        }
+                    }
+                    None => RegionReading::default(),
+                };
+                to_outcome(timestamp, frame_num, region.name.clone(), reading)
+            })
+            .collect();
+
+        // Composite regions join their children's text, then apply their own expectation.
+        // A composite is evaluated on frames where all of its children were read.
+        if !params.config.composites.is_empty() {
+            let children: HashMap<&str, &Measurement> = outcomes
+                .iter()
+                .map(|(m, _)| (m.region_name.as_str(), m))
+                .collect();
+            let joined: Vec<(Measurement, RegionProgress)> = params
+                .config
+                .composites
+                .iter()
+                .filter(|c| {
+                    c.children()
+                        .is_ok_and(|names| names.iter().all(|n| children.contains_key(n)))
+                })
+                .map(|c| {
+                    let mut confidence = 1.0f64;
+                    let text = c.join(|child| {
+                        let m = children.get(child).filter(|m| !m.value.is_empty())?;
+                        confidence = confidence.min(m.confidence);
+                        Some(m.value.clone())
+                    });
+                    let reading = match text {
+                        Some(text) => interpret_text(
+                            &text,
+                            confidence,
+                            "composite",
+                            params.config.expectations.get(&c.name),
+                            prev_snap.get(&c.name).copied(),
+                        ),
+                        None => RegionReading::default(),
+                    };
+                    to_outcome(timestamp, frame_num, c.name.clone(), reading)
+                })
+                .collect();
+            outcomes.extend(joined);
+        }
+
+        // Derived channels read the latest numeric readings (unit-normalised where set).
+        // A region read on this frame replaces its held value; a failed read clears it.
+        if !derived.is_empty() {
+            for (m, _) in &outcomes {
+                match m.normalized_value.or_else(|| m.value.parse::<f64>().ok()) {
+                    Some(value) => latest.insert(
+                        m.region_name.clone(),
+                        Sample {
+                            timestamp,
+                            value,
+                            confidence: m.confidence,
+                        },
+                    ),
+                    None => latest.remove(&m.region_name),
+                };
+            }
+            for d in derived.evaluate(timestamp, &latest) {
+                let reading = RegionReading {
+                    value: d.value.map(format_value).unwrap_or_default(),
+                    confidence: d.confidence,
+                    raw_text: d.expression,
+                    engine_name: "derived".to_string(),
+                    normalized: d.value,
+                    ..Default::default()
+                };
+                outcomes.push(to_outcome(timestamp, frame_num, d.name, reading));
+            }
+        }
 
         // Update prev_values with successfully parsed readings from this frame.
+        // Unit-normalised values are preferred so deviation checks compare like with like.
         for (m, _) in &outcomes {
-            if let Ok(v) = m.value.parse::<f64>() {
+            if let Some(v) = m.normalized_value.or_else(|| m.value.parse::<f64>().ok()) {
                 prev_values.insert(m.region_name.clone(), v);
             }
         }
 
-        measurements.extend(outcomes.into_iter().map(|(m, _)| m));
+        outcomes.retain(|(m, _)| !hidden_children.contains(&m.region_name));
+
+        // Emit one batched event for the entire frame (reduces IPC calls by N_regions).
+        on_progress(&ExtractProgress {
+            frame: frame_num,
+            total: total_steps,
+            timestamp,
+            elapsed_frames: elapsed,
+            regions: outcomes.iter().map(|(_, rp)| rp.clone()).collect(),
+        });
+
+        measurements.extend(outcomes.into_iter().map(|(mut m, _)| {
+            if !params.export_symbols {
+                m.symbols.clear();
+            }
+            m
+        }));
         elapsed += 1;
-        frame_num += fps_sample;
+        frame_num += step;
     }
 
     // ── Build CSV string (not written to disk — user exports explicitly) ──────
 
-    let mut csv =
-        String::from("timestamp,frame_number,region_name,value,confidence,raw_text,source\n");
-    for m in &measurements {
-        csv.push_str(&format!(
-            "{},{},{},{},{:.4},{},{}\n",
-            m.timestamp, m.frame_number, m.region_name, m.value, m.confidence, m.raw_text, m.source,
-        ));
+    let csv = build_csv(&measurements, params.export_symbols);
+    Ok(ExtractResult {
+        measurements,
+        csv,
+        warnings,
+    })
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Pair a region's reading with its progress-event entry.
+fn to_outcome(
+    timestamp: f64,
+    frame_number: u64,
+    region_name: String,
+    reading: RegionReading,
+) -> (Measurement, RegionProgress) {
+    (
+        Measurement {
+            timestamp,
+            frame_number,
+            region_name: region_name.clone(),
+            value: reading.value.clone(),
+            confidence: reading.confidence,
+            raw_text: reading.raw_text,
+            source: reading.engine_name.clone(),
+            unit: reading.unit,
+            normalized_value: reading.normalized,
+            substitutions: reading.substitutions,
+            punctuation: reading.punctuation,
+            symbols: reading.symbols.clone(),
+            skew: reading.skew,
+        },
+        RegionProgress {
+            region_name,
+            value: reading.value,
+            confidence: reading.confidence,
+            ocr_preview: reading.preview_b64,
+            source: reading.engine_name,
+            symbols: reading.symbols,
+            skew: reading.skew,
+        },
+    )
+}
+
+pub const CSV_HEADER: &str =
+    "timestamp,frame_number,region_name,value,confidence,raw_text,source,unit,normalized_value,substitutions,punctuation";
+
+/// `CSV_HEADER`, plus the `symbols` column when per-symbol detail is exported.
+pub fn csv_header(symbols: bool) -> String {
+    if symbols {
+        format!("{CSV_HEADER},symbols")
+    } else {
+        CSV_HEADER.to_string()
+    }
+}
+
+fn build_csv(measurements: &[Measurement], symbols: bool) -> String {
+    let mut csv = csv_header(symbols) + "\n";
+    for m in measurements {
+        csv.push_str(&csv_row(m, symbols));
+        csv.push('\n');
     }
+    csv
+}
 
-    Ok(ExtractResult { measurements, csv })
+/// One CSV line (without terminator) in `csv_header(symbols)` column order.
+pub fn csv_row(m: &Measurement, symbols: bool) -> String {
+    let row = format!(
+        "{},{},{},{},{:.4},{},{},{},{},{},{}",
+        m.timestamp,
+        m.frame_number,
+        csv_field(&m.region_name),
+        csv_field(&m.value),
+        m.confidence,
+        csv_field(&m.raw_text),
+        csv_field(&m.source),
+        csv_field(&m.unit),
+        m.normalized_value
+            .map(|v| v.to_string())
+            .unwrap_or_default(),
+        csv_field(&m.substitutions.join(" ")),
+        csv_field(m.punctuation.as_deref().unwrap_or_default()),
+    );
+    if !symbols {
+        return row;
+    }
+    let detail = m
+        .symbols
+        .iter()
+        .map(|s| format!("{}:{:.2}", s.text, s.confidence))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("{row},{}", csv_field(&detail))
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+/// (derived-channel formulas like `max(a, b)` contain commas).
+pub fn csv_field(s: &str) -> std::borrow::Cow<'_, str> {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\"")).into()
+    } else {
+        s.into()
+    }
 }
 
 /// Write CSV content to the given path, creating parent directories if needed.
@@ -0,0 +1,664 @@
+use crate::config::{load_config, Region, RegionConfig, Resolution};
+use crate::ocr::{
+    calibration::{Calibration, CalibrationMethod, EngineCalibration, MIN_SAMPLES},
+    crop_region, matches_truth, recognize_all,
+    template::{GlyphAccuracy, GlyphModel},
+    Selection,
+};
+use crate::processor::{
+    csv_field, csv_header, csv_row, load_glyphs, run_extraction, CancelFlag, EngineContext,
+    ExtractParams, ExtractResult,
+};
+use image::DynamicImage;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter};
+
+// ── Project format ────────────────────────────────────────────────────────────
+//
+// A project groups the videos of one test campaign.  They usually share a
+// region layout (`config`), with each entry nudging it by a small offset and
+// overriding a few extraction settings.
+
+/// Extraction settings an entry (or the project as a whole) may override.
+/// Unset fields fall back to the project `defaults`, then to built-in values.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ExtractOverrides {
+    pub fps_sample: Option<u32>,
+    pub preprocess: Option<bool>,
+    pub languages: Option<Vec<String>>,
+    pub oar_confidence_threshold: Option<f64>,
+    pub selection: Option<Selection>,
+    pub export_symbols: Option<bool>,
+}
+
+impl ExtractOverrides {
+    /// Fields set in `self` win over those in `base`.
+    fn over(&self, base: &ExtractOverrides) -> ExtractOverrides {
+        ExtractOverrides {
+            fps_sample: self.fps_sample.or(base.fps_sample),
+            preprocess: self.preprocess.or(base.preprocess),
+            languages: self.languages.clone().or_else(|| base.languages.clone()),
+            oar_confidence_threshold: self
+                .oar_confidence_threshold
+                .or(base.oar_confidence_threshold),
+            selection: self.selection.or(base.selection),
+            export_symbols: self.export_symbols.or(base.export_symbols),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectEntry {
+    /// Identifier written to the combined dataset.  Defaults to the video file stem.
+    pub id: Option<String>,
+    pub video_path: String,
+    /// Region layout for this video only.  Absent → `config_path`, then the project's `config`.
+    pub config: Option<RegionConfig>,
+    /// Config file to load instead of embedding one (relative to the project file).
+    pub config_path: Option<String>,
+    /// Pixel offset applied to every region of the layout.
+    #[serde(default)]
+    pub offset_x: i32,
+    #[serde(default)]
+    pub offset_y: i32,
+    #[serde(default)]
+    pub overrides: ExtractOverrides,
+}
+
+impl ProjectEntry {
+    fn video_id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| {
+            Path::new(&self.video_path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| self.video_path.clone())
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    /// Layout shared by every entry that does not bring its own.
+    pub config: Option<RegionConfig>,
+    /// Extraction settings shared by every entry.
+    #[serde(default)]
+    pub defaults: ExtractOverrides,
+    pub entries: Vec<ProjectEntry>,
+    /// Directory holding labeled glyph crops and the trained template model.
+    /// Absent → `<project name>.glyphs` next to the project file.
+    #[serde(default)]
+    pub glyph_dir: Option<String>,
+    /// Ground-truth readings used to calibrate and tune the engines.
+    #[serde(default)]
+    pub labels: Vec<FrameLabel>,
+    /// Per-engine confidence calibration fitted by `calibrate_project`.
+    #[serde(default)]
+    pub calibration: Option<Calibration>,
+}
+
+/// The true text of one region on one frame of a project video.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameLabel {
+    /// Project entry id (see `ProjectEntry::id`).
+    pub video_id: String,
+    pub timestamp: f64,
+    pub region: String,
+    pub text: String,
+}
+
+impl Project {
+    /// Reject entry ids that are repeated or cannot name a file in the
+    /// output directory (`<id>.csv`).
+    fn check_ids(&self) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        for entry in &self.entries {
+            let id = entry.video_id();
+            if id.is_empty() || id == "." || id == ".." || id.contains(['/', '\\']) {
+                return Err(format!("Invalid video id '{id}' in project"));
+            }
+            if !seen.insert(id.clone()) {
+                return Err(format!("Duplicate video id '{id}' in project"));
+            }
+        }
+        Ok(())
+    }
+
+    /// The glyph directory, resolved against the project file.
+    pub(crate) fn glyph_dir(&self, project_path: &Path) -> String {
+        match &self.glyph_dir {
+            Some(g) => resolve_path(project_path, g),
+            None => {
+                let stem = project_path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "project".to_string());
+                resolve_path(project_path, &format!("{stem}.glyphs"))
+            }
+        }
+    }
+
+    /// Build the extraction parameters for every entry, with paths resolved
+    /// against the project file and `config_path` layouts loaded.  The project
+    /// itself is left as stored, so it can be saved back unchanged.
+    pub(crate) fn resolve(
+        &self,
+        project_path: &Path,
+    ) -> Result<Vec<(String, ExtractParams)>, String> {
+        self.check_ids()?;
+        let glyph_dir = self.glyph_dir(project_path);
+        self.entries
+            .iter()
+            .map(|entry| {
+                let id = entry.video_id();
+                let video_path = resolve_path(project_path, &entry.video_path);
+                let layout = match (&entry.config, &entry.config_path) {
+                    (Some(config), _) => Some(config.clone()),
+                    (None, Some(cp)) => Some(load_config(resolve_path(project_path, cp))?),
+                    (None, None) => self.config.clone(),
+                };
+                let mut config =
+                    layout.ok_or_else(|| format!("Video '{id}' has no region config"))?;
+                config.sort_keyframes();
+                config.offset_regions(entry.offset_x, entry.offset_y);
+
+                let settings = entry.overrides.over(&self.defaults);
+                let fps_sample = match settings.fps_sample {
+                    Some(n) => n,
+                    None => crate::video::get_video_info(video_path.clone())?
+                        .fps
+                        .round() as u32,
+                };
+                let params = ExtractParams {
+                    video_path,
+                    config,
+                    fps_sample,
+                    preprocess: settings.preprocess.unwrap_or(true),
+                    languages: settings.languages.unwrap_or_else(|| vec!["en".to_string()]),
+                    oar_confidence_threshold: settings.oar_confidence_threshold.unwrap_or(0.9),
+                    glyph_dir: Some(glyph_dir.clone()),
+                    selection: settings.selection.unwrap_or_default(),
+                    calibration: self.calibration.clone(),
+                    export_symbols: settings.export_symbols.unwrap_or(false),
+                };
+                Ok((id, params))
+            })
+            .collect()
+    }
+}
+
+/// `p` as stored in the project, made absolute against the project file's directory.
+pub(crate) fn resolve_path(project_path: &Path, p: &str) -> String {
+    let path = Path::new(p);
+    if path.is_absolute() {
+        p.to_string()
+    } else {
+        let dir = project_path.parent().unwrap_or(Path::new("."));
+        dir.join(path).to_string_lossy().to_string()
+    }
+}
+
+/// Read a project as stored: paths stay relative and `config_path` layouts
+/// are not inlined (see `Project::resolve`).
+#[tauri::command]
+pub fn load_project(path: String) -> Result<Project, String> {
+    let text = fs::read_to_string(&path).map_err(|e| format!("Cannot read {path}: {e}"))?;
+    let project: Project =
+        serde_json::from_str(&text).map_err(|e| format!("Parse error in {path}: {e}"))?;
+    project.check_ids()?;
+    Ok(project)
+}
+
+#[tauri::command]
+pub fn save_project(path: String, project: Project) -> Result<(), String> {
+    project.check_ids()?;
+    if let Some(parent) = Path::new(&path).parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Cannot create dirs: {e}"))?;
+    }
+    let text =
+        serde_json::to_string_pretty(&project).map_err(|e| format!("Serialise error: {e}"))?;
+    fs::write(&path, text).map_err(|e| format!("Cannot write {path}: {e}"))
+}
+
+// ── Batch extraction ──────────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectExtractParams {
+    pub project_path: String,
+    /// Extract several videos at once instead of one after another.
+    #[serde(default)]
+    pub parallel: bool,
+    /// Directory receiving `<video id>.csv` per video and `combined.csv`.
+    /// Absent → nothing is written (the combined CSV is still returned).
+    pub output_dir: Option<String>,
+}
+
+/// Progress of one video inside a project run.
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectProgress {
+    pub video_id: String,
+    pub video_index: usize,
+    pub total_videos: usize,
+    /// "running", "done" or "failed".
+    pub status: &'static str,
+    pub elapsed_frames: u64,
+    pub total_frames: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VideoOutcome {
+    pub id: String,
+    pub video_path: String,
+    pub measurements: usize,
+    pub csv_path: Option<String>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectResult {
+    pub videos: Vec<VideoOutcome>,
+    /// Every video's measurements with a leading `video_id` column.
+    pub combined_csv: String,
+}
+
+#[tauri::command]
+pub async fn extract_project(
+    app: AppHandle,
+    params: ProjectExtractParams,
+    cancel: tauri::State<'_, CancelFlag>,
+) -> Result<ProjectResult, String> {
+    let project_path = Path::new(&params.project_path);
+    let plan = load_project(params.project_path.clone())?.resolve(project_path)?;
+    let out_dir = params.output_dir.as_ref().map(PathBuf::from);
+    if let Some(dir) = &out_dir {
+        fs::create_dir_all(dir).map_err(|e| format!("Cannot create directories: {e}"))?;
+    }
+
+    let engines = EngineContext::locate(&app);
+    let flag = cancel.0.clone();
+    flag.store(false, Ordering::Relaxed);
+    let total_videos = plan.len();
+    // One column layout for the combined CSV: symbols if any video exports them.
+    let symbols = plan.iter().any(|(_, p)| p.export_symbols);
+
+    // One failing video does not abort the others; its error is reported instead.
+    let run = |(index, (id, params)): (usize, (String, ExtractParams))| {
+        let video_path = params.video_path.clone();
+        let progress = |status, elapsed_frames, total_frames, error| ProjectProgress {
+            video_id: id.clone(),
+            video_index: index,
+            total_videos,
+            status,
+            elapsed_frames,
+            total_frames,
+            error,
+        };
+        let result = run_extraction(params, &engines, &flag, &|p| {
+            let _ = app.emit(
+                "project_progress",
+                progress("running", p.elapsed_frames, p.total, None),
+            );
+        });
+        let done = match &result {
+            Ok(_) => progress("done", 0, 0, None),
+            Err(e) => progress("failed", 0, 0, Some(e.clone())),
+        };
+        let _ = app.emit("project_progress", done);
+        (id, video_path, result)
+    };
+    let results: Vec<(String, String, Result<ExtractResult, String>)> = if params.parallel {
+        plan.into_par_iter().enumerate().map(run).collect()
+    } else {
+        plan.into_iter().enumerate().map(run).collect()
+    };
+
+    let mut combined_csv = format!("video_id,{}\n", csv_header(symbols));
+    let mut videos = Vec::with_capacity(results.len());
+    for (id, video_path, result) in results {
+        let outcome = match result {
+            Ok(res) => {
+                for m in &res.measurements {
+                    combined_csv.push_str(&format!("{},{}\n", csv_field(&id), csv_row(m, symbols)));
+                }
+                let csv_path = match &out_dir {
+                    Some(dir) => {
+                        let path = dir.join(format!("{id}.csv"));
+                        fs::write(&path, &res.csv)
+                            .map_err(|e| format!("Cannot write {}: {e}", path.display()))?;
+                        Some(path.to_string_lossy().to_string())
+                    }
+                    None => None,
+                };
+                VideoOutcome {
+                    id,
+                    video_path,
+                    measurements: res.measurements.len(),
+                    csv_path,
+                    warnings: res.warnings,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                eprintln!("project video '{id}' failed: {e}");
+                VideoOutcome {
+                    id,
+                    video_path,
+                    measurements: 0,
+                    csv_path: None,
+                    warnings: vec![],
+                    error: Some(e),
+                }
+            }
+        };
+        videos.push(outcome);
+    }
+
+    if let Some(dir) = &out_dir {
+        let path = dir.join("combined.csv");
+        fs::write(&path, &combined_csv)
+            .map_err(|e| format!("Cannot write {}: {e}", path.display()))?;
+    }
+
+    Ok(ProjectResult {
+        videos,
+        combined_csv,
+    })
+}
+
+// ── Glyph training ────────────────────────────────────────────────────────────
+//
+// Labeled crops are stored as PNGs in the project's glyph directory, indexed
+// by `samples.json`.  `retrain_glyphs` rebuilds the template model
+// (`ocr::template`) from all of them.
+
+const SAMPLES_FILE: &str = "samples.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlyphSample {
+    /// PNG file name inside the glyph directory.
+    pub file: String,
+    /// The text shown in the crop, one character per glyph.
+    pub label: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GlyphReport {
+    pub samples: usize,
+    /// Samples left out of training, with the reason.
+    pub skipped: Vec<String>,
+    /// Leave-one-out accuracy per glyph.
+    pub glyphs: Vec<GlyphAccuracy>,
+}
+
+fn glyph_dir(project_path: &str) -> Result<PathBuf, String> {
+    let project = load_project(project_path.to_string())?;
+    Ok(PathBuf::from(project.glyph_dir(Path::new(project_path))))
+}
+
+fn load_samples(dir: &Path) -> Result<Vec<GlyphSample>, String> {
+    let path = dir.join(SAMPLES_FILE);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let text =
+        fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+    serde_json::from_str(&text).map_err(|e| format!("Parse error in {}: {e}", path.display()))
+}
+
+/// Store the crop of `region` at `timestamp` with its true text.
+/// Returns the number of samples now stored.
+#[tauri::command]
+pub fn add_glyph_sample(
+    project_path: String,
+    video_path: String,
+    timestamp: f64,
+    region: Region,
+    label: String,
+) -> Result<usize, String> {
+    let label = label.trim().to_string();
+    if label.is_empty() {
+        return Err("Sample label is empty".to_string());
+    }
+    let dir = glyph_dir(&project_path)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Cannot create directories: {e}"))?;
+
+    let (bytes, fw, fh) = crate::video::decode_frame_at(&video_path, timestamp)?;
+    let frame = image::RgbImage::from_raw(fw, fh, bytes)
+        .ok_or_else(|| "Decoded frame has an unexpected size".to_string())?;
+    let x = (region.x.max(0) as u32).min(fw);
+    let y = (region.y.max(0) as u32).min(fh);
+    let w = (region.width.max(0) as u32).min(fw - x);
+    let h = (region.height.max(0) as u32).min(fh - y);
+    if w == 0 || h == 0 {
+        return Err(format!("Region '{}' lies outside the frame", region.name));
+    }
+    let crop = image::imageops::crop_imm(&frame, x, y, w, h).to_image();
+
+    let mut samples = load_samples(&dir)?;
+    // After a deletion `len + 1` may still name a stored sample; skip taken names.
+    let file = (samples.len() + 1..)
+        .map(|n| format!("sample-{n:04}.png"))
+        .find(|f| !samples.iter().any(|s| &s.file == f) && !dir.join(f).exists())
+        .expect("unbounded range");
+    crop.save(dir.join(&file))
+        .map_err(|e| format!("Cannot write {file}: {e}"))?;
+    samples.push(GlyphSample { file, label });
+
+    let text =
+        serde_json::to_string_pretty(&samples).map_err(|e| format!("Serialise error: {e}"))?;
+    fs::write(dir.join(SAMPLES_FILE), text).map_err(|e| format!("Cannot write samples: {e}"))?;
+    Ok(samples.len())
+}
+
+/// Rebuild the template model from every stored sample.
+#[tauri::command]
+pub fn retrain_glyphs(project_path: String) -> Result<GlyphReport, String> {
+    let dir = glyph_dir(&project_path)?;
+    let samples = load_samples(&dir)?;
+    if samples.is_empty() {
+        return Err("No labeled glyph samples yet".to_string());
+    }
+
+    let mut model = GlyphModel::default();
+    let mut skipped = Vec::new();
+    for s in &samples {
+        let img = image::open(dir.join(&s.file))
+            .map_err(|e| format!("Cannot read sample {}: {e}", s.file))?;
+        if let Err(e) = model.learn(&img, &s.label) {
+            skipped.push(format!("{} ({:?}): {e}", s.file, s.label));
+        }
+    }
+    model.save(&dir)?;
+
+    Ok(GlyphReport {
+        samples: samples.len(),
+        skipped,
+        glyphs: model.accuracy(),
+    })
+}
+
+/// Per-glyph accuracy of the current template model.
+#[tauri::command]
+pub fn glyph_accuracy(project_path: String) -> Result<GlyphReport, String> {
+    let dir = glyph_dir(&project_path)?;
+    let model = GlyphModel::load(&dir)?;
+    Ok(GlyphReport {
+        samples: load_samples(&dir)?.len(),
+        skipped: vec![],
+        glyphs: model.accuracy(),
+    })
+}
+
+// ── Ground truth ──────────────────────────────────────────────────────────────
+
+/// A labeled region crop with the extraction settings of its video.
+pub(crate) struct LabeledCrop<'a> {
+    pub label: &'a FrameLabel,
+    pub crop: DynamicImage,
+    pub params: &'a ExtractParams,
+}
+
+/// Extraction settings per video id, with calibration removed (callers want
+/// raw engine output).
+pub(crate) fn label_params(
+    project: &Project,
+    project_path: &Path,
+) -> Result<HashMap<String, ExtractParams>, String> {
+    Ok(project
+        .resolve(project_path)?
+        .into_iter()
+        .filter(|(id, _)| project.labels.iter().any(|l| &l.video_id == id))
+        .map(|(id, mut params)| {
+            params.calibration = None;
+            (id, params)
+        })
+        .collect())
+}
+
+/// Decode and crop every labeled region.
+pub(crate) fn labeled_crops<'a>(
+    project: &'a Project,
+    params: &'a HashMap<String, ExtractParams>,
+) -> Result<Vec<LabeledCrop<'a>>, String> {
+    project
+        .labels
+        .par_iter()
+        .map(|label| {
+            let p = params
+                .get(&label.video_id)
+                .ok_or_else(|| format!("Label refers to unknown video '{}'", label.video_id))?;
+            let (bytes, fw, fh) = crate::video::decode_frame_at(&p.video_path, label.timestamp)?;
+            let frame_size = Resolution {
+                width: fw,
+                height: fh,
+            };
+            let region = p
+                .config
+                .get_regions_at(label.timestamp, frame_size)
+                .into_iter()
+                .find(|r| r.name == label.region)
+                .ok_or_else(|| {
+                    format!(
+                        "Region '{}' does not exist at {:.3}s in '{}'",
+                        label.region, label.timestamp, label.video_id
+                    )
+                })?;
+            let crop = crop_region(
+                &bytes,
+                fw,
+                fh,
+                region.x.max(0) as u32,
+                region.y.max(0) as u32,
+                region.width.max(0) as u32,
+                region.height.max(0) as u32,
+            )
+            .ok_or_else(|| format!("Region '{}' lies outside the frame", region.name))?;
+            Ok(LabeledCrop {
+                label,
+                crop,
+                params: p,
+            })
+        })
+        .collect()
+}
+
+// ── Calibration ───────────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct EngineCalibrationReport {
+    pub engine: String,
+    pub samples: usize,
+    /// Fraction of labeled readings the engine got right.
+    pub accuracy: f64,
+    /// `false` when there were fewer than `MIN_SAMPLES` readings.
+    pub calibrated: bool,
+}
+
+/// Run every engine on every labeled region, fit a confidence mapping per
+/// engine and store it in the project file.
+#[tauri::command]
+pub async fn calibrate_project(
+    app: AppHandle,
+    project_path: String,
+    method: Option<CalibrationMethod>,
+) -> Result<Vec<EngineCalibrationReport>, String> {
+    let project = load_project(project_path.clone())?;
+    if project.labels.is_empty() {
+        return Err("The project has no ground-truth labels".to_string());
+    }
+    let path = Path::new(&project_path);
+    let params = label_params(&project, path)?;
+    let crops = labeled_crops(&project, &params)?;
+
+    let engines = EngineContext::locate(&app);
+    let glyphs = load_glyphs(Some(&project.glyph_dir(path)))?;
+    let mut sets = HashMap::new();
+    for c in &crops {
+        let key = (c.label.video_id.as_str(), c.label.region.as_str());
+        if !sets.contains_key(&key) {
+            let set = engines.engine_set(c.params, Some(&c.label.region), glyphs.as_ref())?;
+            sets.insert(key, set);
+        }
+    }
+
+    // (engine, raw confidence, correct) for every engine output.
+    let outcomes: Vec<(String, f64, bool)> = crops
+        .par_iter()
+        .flat_map_iter(|c| {
+            let set = &sets[&(c.label.video_id.as_str(), c.label.region.as_str())];
+            let expectation = c.params.config.expectations.get(&c.label.region);
+            let crop = set.prepare_crop(c.crop.clone(), expectation, None);
+            let priority = crop.priority.unwrap_or_else(|| {
+                recognize_all(&set.priority, std::slice::from_ref(&crop.image))
+                    .pop()
+                    .unwrap_or_default()
+            });
+            priority
+                .into_iter()
+                .chain(set.fallback.iter().filter_map(|e| e.recognize(&crop.image)))
+                .map(|r| {
+                    let ok = matches_truth(&r.text, &c.label.text, expectation);
+                    (r.engine_name, r.confidence, ok)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let mut samples: HashMap<String, Vec<(f64, bool)>> = HashMap::new();
+    for (engine, conf, ok) in outcomes {
+        samples.entry(engine).or_default().push((conf, ok));
+    }
+
+    let method = method.unwrap_or_default();
+    let mut calibration = Calibration::default();
+    let mut report: Vec<EngineCalibrationReport> = samples
+        .into_iter()
+        .map(|(engine, s)| {
+            let calibrated = s.len() >= MIN_SAMPLES;
+            if calibrated {
+                calibration
+                    .engines
+                    .insert(engine.clone(), EngineCalibration::fit(method, &s));
+            }
+            EngineCalibrationReport {
+                accuracy: s.iter().filter(|x| x.1).count() as f64 / s.len() as f64,
+                samples: s.len(),
+                engine,
+                calibrated,
+            }
+        })
+        .collect();
+    report.sort_by(|a, b| a.engine.cmp(&b.engine));
+
+    let mut project = project;
+    project.calibration = Some(calibration);
+    save_project(project_path, project)?;
+
+    Ok(report)
+}
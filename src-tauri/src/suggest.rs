@@ -0,0 +1,219 @@
+use image::{DynamicImage, RgbImage};
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::config::{Region, RegionExpectation};
+use crate::ocr::{
+    clean_number,
+    oar::{ColorMode, OarRecognizer, TextBox},
+    recognize_all, units, Recognizer,
+};
+use crate::processor::EngineContext;
+use crate::video::decode_frame_at;
+
+// ── Region suggestion ─────────────────────────────────────────────────────────
+//
+// Runs the text detector over a whole frame, merges boxes that belong to one
+// text line (digits detected separately), OCRs every line and proposes a
+// region for each.  Numbers get an expectation guessed from how they print;
+// plain words are offered too and double as labels that name the nearest
+// number to their right or below.
+
+/// Boxes on one line merge when the gap between them is below this fraction
+/// of the taller box's height.
+const MERGE_GAP: f64 = 0.5;
+/// …and their vertical overlap is at least this fraction of the shorter height.
+const MERGE_OVERLAP: f64 = 0.5;
+/// A label names a number at most this many number-heights away.
+const LABEL_REACH: f64 = 3.0;
+
+/// One proposed region; the frontend accepts or rejects each.
+#[derive(Debug, Serialize)]
+pub struct RegionSuggestion {
+    pub region: Region,
+    /// What the OCR read in it.
+    pub text: String,
+    pub confidence: f64,
+    /// Guessed from `text`; `None` for non-numeric text.
+    pub expectation: Option<RegionExpectation>,
+}
+
+#[tauri::command]
+pub async fn suggest_regions(
+    app: tauri::AppHandle,
+    path: String,
+    timestamp: f64,
+) -> Result<Vec<RegionSuggestion>, String> {
+    let engines = EngineContext::locate(&app);
+    let pipeline = engines
+        .oar()
+        .filter(|p| p.has_detector())
+        .ok_or("Text detection model not available")?
+        .clone();
+
+    let (rgb, w, h) = decode_frame_at(&path, timestamp)?;
+    let frame = RgbImage::from_raw(w, h, rgb).ok_or("Decoded frame has the wrong size")?;
+    let boxes = pipeline
+        .detect(&frame)
+        .ok_or("Text detection failed on this frame")?;
+    let lines = merge_lines(boxes);
+
+    // Read every line with both oar-ocr colour modes, keeping the better.
+    let recognizers: Vec<Box<dyn Recognizer>> = [ColorMode::Rgb, ColorMode::Grayscale]
+        .into_iter()
+        .map(|color_mode| {
+            Box::new(OarRecognizer::new(pipeline.clone(), color_mode, None, None))
+                as Box<dyn Recognizer>
+        })
+        .collect();
+    let frame = DynamicImage::ImageRgb8(frame);
+    let crops: Vec<DynamicImage> = lines
+        .iter()
+        .map(|b| frame.crop_imm(b.x, b.y, b.width, b.height))
+        .collect();
+    let readings: Vec<(TextBox, String, f64)> = lines
+        .into_iter()
+        .zip(recognize_all(&recognizers, &crops))
+        .filter_map(|(b, results)| {
+            let best = results.into_iter().max_by(|a, b| {
+                a.confidence
+                    .partial_cmp(&b.confidence)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })?;
+            Some((b, best.text.trim().to_string(), best.confidence))
+        })
+        .filter(|(_, text, _)| !text.is_empty())
+        .collect();
+
+    let mut taken: HashSet<String> = HashSet::new();
+    let mut suggestions: Vec<RegionSuggestion> = readings
+        .iter()
+        .map(|(b, text, confidence)| {
+            let expectation = guess_expectation(text);
+            let base = match &expectation {
+                Some(exp) => label_for(b, &readings)
+                    .or_else(|| exp.quantity.clone())
+                    .unwrap_or_else(|| "value".to_string()),
+                None => slug(text).unwrap_or_else(|| "text".to_string()),
+            };
+            RegionSuggestion {
+                region: Region {
+                    name: unique_name(&base, &mut taken),
+                    x: b.x as i32,
+                    y: b.y as i32,
+                    width: b.width as i32,
+                    height: b.height as i32,
+                },
+                text: text.clone(),
+                confidence: *confidence,
+                expectation,
+            }
+        })
+        .collect();
+
+    // Numbers first, then reading order.
+    suggestions.sort_by_key(|s| (s.expectation.is_none(), s.region.y, s.region.x));
+    eprintln!(
+        "[suggest] {} suggestion(s) at t={timestamp:.2}s",
+        suggestions.len()
+    );
+    Ok(suggestions)
+}
+
+/// Merge boxes that sit side by side on one text line until none do.
+fn merge_lines(mut boxes: Vec<TextBox>) -> Vec<TextBox> {
+    'merge: loop {
+        for i in 0..boxes.len() {
+            for j in i + 1..boxes.len() {
+                if same_line(&boxes[i], &boxes[j]) {
+                    let b = boxes.swap_remove(j);
+                    boxes[i] = union(&boxes[i], &b);
+                    continue 'merge;
+                }
+            }
+        }
+        return boxes;
+    }
+}
+
+fn same_line(a: &TextBox, b: &TextBox) -> bool {
+    let (a_bottom, b_bottom) = ((a.y + a.height) as f64, (b.y + b.height) as f64);
+    let (a_right, b_right) = ((a.x + a.width) as f64, (b.x + b.width) as f64);
+    let overlap = a_bottom.min(b_bottom) - (a.y.max(b.y) as f64);
+    let gap = (a.x.max(b.x) as f64) - a_right.min(b_right);
+    overlap >= MERGE_OVERLAP * a.height.min(b.height) as f64
+        && gap <= MERGE_GAP * a.height.max(b.height) as f64
+}
+
+fn union(a: &TextBox, b: &TextBox) -> TextBox {
+    let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+    let right = (a.x + a.width).max(b.x + b.width);
+    let bottom = (a.y + a.height).max(b.y + b.height);
+    TextBox {
+        x,
+        y,
+        width: right - x,
+        height: bottom - y,
+        score: a.score.max(b.score),
+    }
+}
+
+/// An expectation for `text` if it reads as a number: the decimal places and
+/// digit count as printed, and the quantity of a recognised unit suffix.
+fn guess_expectation(text: &str) -> Option<RegionExpectation> {
+    let number = clean_number(text);
+    number.parse::<f64>().ok()?;
+    let quantity = units::parse_unit(&units::unit_suffix(text, |_| false, false))
+        .map(|u| u.unit.quantity.to_string());
+    Some(RegionExpectation {
+        numeric: true,
+        decimal_places: Some(number.split_once('.').map_or(0, |(_, f)| f.len() as u32)),
+        total_digits: Some(number.chars().filter(char::is_ascii_digit).count() as u32),
+        quantity,
+        ..Default::default()
+    })
+}
+
+/// The nearest word-only line left of `number` on the same line, or above it.
+fn label_for(number: &TextBox, readings: &[(TextBox, String, f64)]) -> Option<String> {
+    let reach = LABEL_REACH * number.height as f64;
+    readings
+        .iter()
+        .filter(|(_, text, _)| !text.chars().any(|c| c.is_ascii_digit()))
+        .filter_map(|(b, text, _)| {
+            let vertical =
+                (b.y + b.height).min(number.y + number.height) as i64 - b.y.max(number.y) as i64;
+            let horizontal =
+                (b.x + b.width).min(number.x + number.width) as i64 - b.x.max(number.x) as i64;
+            let distance = if vertical > 0 && b.x + b.width <= number.x {
+                number.x - (b.x + b.width)
+            } else if horizontal > 0 && b.y + b.height <= number.y {
+                number.y - (b.y + b.height)
+            } else {
+                return None;
+            };
+            (distance as f64 <= reach).then(|| (distance, slug(text)))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .and_then(|(_, name)| name)
+}
+
+/// Lower-case identifier from display text ("Temp. 1" → "temp_1").
+fn slug(text: &str) -> Option<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    (!words.is_empty()).then(|| words.join("_"))
+}
+
+fn unique_name(base: &str, taken: &mut HashSet<String>) -> String {
+    let mut name = base.to_string();
+    let mut n = 2;
+    while !taken.insert(name.clone()) {
+        name = format!("{base}_{n}");
+        n += 1;
+    }
+    name
+}
@@ -0,0 +1,474 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::{load_config, save_config, RegionConfig, RegionSettings};
+use crate::ocr::{
+    matches_truth,
+    preprocess::{Channel, MorphOp, Pipeline, PipelineSpec, Step},
+    read_region, recognize_all,
+    template::GlyphModel,
+};
+use crate::processor::{load_glyphs, EngineContext};
+use crate::project::{
+    label_params, labeled_crops, load_project, resolve_path, save_project, LabeledCrop, Project,
+};
+
+// ── Region tuning ─────────────────────────────────────────────────────────────
+//
+// Tries preprocessing pipelines and engine choices on one region's labeled
+// frames and keeps the combination that reads the most of them correctly.
+// A trial runs one engine family on one pipeline through the same detection,
+// fast-path and fallback logic as an extraction; the region's current
+// settings are always tried too, so the result can only improve on them.
+
+/// Pipeline parameters searched.  A pipeline takes one value from each list.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TuneSpace {
+    /// Engine selectors tried (see `RegionSettings::engines`).  Pipelines vary
+    /// for "oar-ocr" and "tesseract"; other engines are tried once as-is.
+    pub engines: Vec<String>,
+    /// Upscale factors; 1 keeps the crop size.
+    pub upscale: Vec<u32>,
+    pub channels: Vec<Channel>,
+    /// CLAHE clip limits; 0 skips CLAHE.
+    pub clahe_clip: Vec<f32>,
+    /// Sauvola k values tried as binarisation.
+    pub sauvola_k: Vec<f64>,
+    /// Also try Otsu binarisation.
+    pub otsu: bool,
+    /// Also try leaving the image in grayscale.
+    pub unbinarized: bool,
+    /// Opening radii after binarisation; 0 skips the opening.
+    pub open_radius: Vec<u8>,
+}
+
+impl Default for TuneSpace {
+    fn default() -> Self {
+        TuneSpace {
+            engines: vec!["oar-ocr".to_string(), "tesseract".to_string()],
+            upscale: vec![1, 3, 6],
+            channels: vec![Channel::Luma, Channel::R, Channel::G, Channel::B],
+            clahe_clip: vec![0.0, 2.0, 4.0],
+            sauvola_k: vec![0.2, 0.34, 0.5],
+            otsu: true,
+            unbinarized: true,
+            open_radius: vec![0, 1],
+        }
+    }
+}
+
+fn default_trials() -> usize {
+    64
+}
+
+fn default_save() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TuneParams {
+    pub region: String,
+    #[serde(default)]
+    pub space: TuneSpace,
+    /// Random search: at most this many trials drawn from the grid.
+    /// 0 → the full grid.
+    #[serde(default = "default_trials")]
+    pub trials: usize,
+    /// Seed for drawing trials, so a search can be repeated.
+    #[serde(default)]
+    pub seed: u64,
+    /// Write the winner into the region's settings when it beats them.
+    #[serde(default = "default_save")]
+    pub save: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrialReport {
+    /// "current" for the region's existing settings, otherwise the engine
+    /// selector and pipeline name.
+    pub label: String,
+    pub settings: RegionSettings,
+    pub correct: usize,
+    pub total: usize,
+    pub accuracy: f64,
+    pub mean_confidence: f64,
+    /// Why the trial could not run (e.g. `template` without a glyph model).
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TuneReport {
+    /// Best first.
+    pub trials: Vec<TrialReport>,
+    /// Settings written to the project, if the best trial beat the current ones.
+    pub saved: Option<RegionSettings>,
+}
+
+#[tauri::command]
+pub async fn tune_region(
+    app: tauri::AppHandle,
+    project_path: String,
+    params: TuneParams,
+) -> Result<TuneReport, String> {
+    let project = load_project(project_path.clone())?;
+    let region = params.region.as_str();
+    let mut labeled = project.clone();
+    labeled.labels.retain(|l| l.region == region);
+    if labeled.labels.is_empty() {
+        return Err(format!("Region '{region}' has no ground-truth labels"));
+    }
+    let path = Path::new(&project_path);
+    let extract_params = label_params(&labeled, path)?;
+    let crops = labeled_crops(&labeled, &extract_params)?;
+
+    let engines = EngineContext::locate(&app);
+    let glyphs = load_glyphs(Some(&project.glyph_dir(path)))?;
+    // One winner is written into every layout, so they must start out alike.
+    let settings_of = |c: &LabeledCrop| {
+        c.params
+            .config
+            .region_settings
+            .get(region)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let current = settings_of(&crops[0]);
+    if let Some(other) = crops
+        .iter()
+        .find(|c| !same_settings(&settings_of(c), &current))
+    {
+        return Err(format!(
+            "Region '{region}' has different settings in '{}' and '{}'; give the layouts the same settings before tuning",
+            crops[0].label.video_id, other.label.video_id
+        ));
+    }
+    if current.stack.is_some() {
+        eprintln!("[tune] {region}: labeled frames are read without frame stacking");
+    }
+
+    let mut candidates = grid(&params.space, &current);
+    if params.trials > 0 && candidates.len() > params.trials {
+        shuffle(&mut candidates, params.seed);
+        candidates.truncate(params.trials);
+    }
+    candidates.insert(0, ("current".to_string(), current.clone()));
+    eprintln!(
+        "[tune] {region}: {} trial(s) on {} labeled frame(s)",
+        candidates.len(),
+        crops.len()
+    );
+
+    let mut trials: Vec<TrialReport> = candidates
+        .into_par_iter()
+        .map(|(label, settings)| {
+            let outcome = evaluate(&engines, &crops, region, &settings, glyphs.as_ref());
+            let total = crops.len();
+            let (correct, confidence, error) = match outcome {
+                Ok((correct, confidence)) => (correct, confidence, None),
+                Err(e) => (0, 0.0, Some(e)),
+            };
+            TrialReport {
+                label,
+                settings,
+                correct,
+                total,
+                accuracy: correct as f64 / total as f64,
+                mean_confidence: confidence / total as f64,
+                error,
+            }
+        })
+        .collect();
+    // Accuracy first; confidence separates equally accurate trials.
+    trials.sort_by(|a, b| {
+        (b.correct, b.mean_confidence)
+            .partial_cmp(&(a.correct, a.mean_confidence))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let current_correct = trials
+        .iter()
+        .find(|t| t.label == "current")
+        .map_or(0, |t| t.correct);
+    let best = &trials[0];
+    eprintln!(
+        "[tune] {region}: best {} ({}/{}), current {current_correct}/{}",
+        best.label, best.correct, best.total, best.total
+    );
+    let saved = (params.save && best.error.is_none() && best.correct > current_correct)
+        .then(|| best.settings.clone());
+    if let Some(settings) = &saved {
+        store_settings(&project_path, project, region, &current, settings)?;
+    }
+
+    Ok(TuneReport { trials, saved })
+}
+
+/// Read every labeled crop with `settings` for `region`; returns the number
+/// read correctly and the sum of the readings' confidences.  Labels are single
+/// frames, so `RegionSettings::stack` is not applied; the tuned settings keep
+/// the region's stack as it was.
+fn evaluate(
+    engines: &EngineContext,
+    crops: &[LabeledCrop],
+    region: &str,
+    settings: &RegionSettings,
+    glyphs: Option<&std::sync::Arc<GlyphModel>>,
+) -> Result<(usize, f64), String> {
+    // One engine set per video: their expectations and defaults may differ.
+    let mut sets = HashMap::new();
+    for c in crops {
+        let id = c.label.video_id.as_str();
+        if !sets.contains_key(id) {
+            let mut params = c.params.clone();
+            params
+                .config
+                .region_settings
+                .insert(region.to_string(), settings.clone());
+            sets.insert(id, engines.engine_set(&params, Some(region), glyphs)?);
+        }
+    }
+
+    let (mut correct, mut confidence) = (0, 0.0);
+    for c in crops {
+        let set = &sets[c.label.video_id.as_str()];
+        let expectation = c.params.config.expectations.get(region);
+        let crop = set.prepare_crop(c.crop.clone(), expectation, None);
+        let priority = crop.priority.unwrap_or_else(|| {
+            recognize_all(&set.priority, std::slice::from_ref(&crop.image))
+                .pop()
+                .unwrap_or_default()
+        });
+        let reading = read_region(
+            &crop.image,
+            priority,
+            &set.fallback,
+            set.fast_threshold,
+            set.selection,
+            expectation,
+            None,
+        );
+        if matches_truth(&reading.raw_text, &c.label.text, expectation) {
+            correct += 1;
+        }
+        confidence += reading.confidence;
+    }
+    Ok((correct, confidence))
+}
+
+// ── Search space ──────────────────────────────────────────────────────────────
+
+enum Binarize {
+    None,
+    Otsu,
+    Sauvola(f64),
+}
+
+/// Every (engine, pipeline) combination of `space`, as labelled region settings
+/// derived from `current`.
+fn grid(space: &TuneSpace, current: &RegionSettings) -> Vec<(String, RegionSettings)> {
+    let mut binarize: Vec<(Binarize, u8)> = Vec::new();
+    for &radius in &space.open_radius {
+        binarize.extend(
+            space
+                .sauvola_k
+                .iter()
+                .map(|&k| (Binarize::Sauvola(k), radius)),
+        );
+        if space.otsu {
+            binarize.push((Binarize::Otsu, radius));
+        }
+    }
+    if space.unbinarized {
+        binarize.push((Binarize::None, 0));
+    }
+
+    let mut pipelines = Vec::new();
+    for &factor in &space.upscale {
+        for &channel in &space.channels {
+            for &clip in &space.clahe_clip {
+                for (bin, radius) in &binarize {
+                    pipelines.push(pipeline(factor.max(1), channel, clip, bin, *radius));
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut families: Vec<&str> = Vec::new();
+    for selector in &space.engines {
+        // Tuned pipelines get their own recognizer names, so select the family.
+        let family = ["oar-ocr", "tesseract"]
+            .into_iter()
+            .find(|f| selector == f || selector.starts_with(&format!("{f}/")));
+        let Some(family) = family else {
+            let mut settings = current.clone();
+            settings.engines = Some(vec![selector.clone()]);
+            out.push((selector.clone(), settings));
+            continue;
+        };
+        if families.contains(&family) {
+            continue;
+        }
+        families.push(family);
+        for p in &pipelines {
+            let mut settings = current.clone();
+            settings.engines = Some(vec![family.to_string()]);
+            let specs = Some(vec![PipelineSpec::Custom(p.clone())]);
+            match family {
+                "oar-ocr" => settings.oar_preprocess = specs,
+                _ => settings.preprocess = specs,
+            }
+            out.push((format!("{family}/{}", p.name), settings));
+        }
+    }
+    out
+}
+
+/// A pipeline in the shape of the built-in presets, named after its parameters
+/// (e.g. "up6-r-clahe2-sauvola0.34-open1").
+fn pipeline(factor: u32, channel: Channel, clip: f32, bin: &Binarize, radius: u8) -> Pipeline {
+    let mut name = vec![format!("up{factor}")];
+    let mut steps = Vec::new();
+    if factor > 1 {
+        steps.push(Step::Upscale { factor });
+    }
+    name.push(
+        match channel {
+            Channel::Luma => "luma",
+            Channel::R => "r",
+            Channel::G => "g",
+            Channel::B => "b",
+        }
+        .to_string(),
+    );
+    steps.extend([
+        Step::Channel { channel },
+        Step::Invert {
+            if_mean_below: Some(140),
+        },
+        Step::Gamma {
+            gamma: 0.5,
+            if_mean_below: Some(80),
+        },
+    ]);
+    if clip > 0.0 {
+        name.push(format!("clahe{clip}"));
+        steps.extend([
+            // ~5 original pixels per tile, as in the presets.
+            Step::Clahe {
+                tile: (5 * factor).max(8),
+                clip_limit: clip,
+            },
+            Step::Blur { sigma: 1.0 },
+        ]);
+    }
+    match *bin {
+        Binarize::None => {}
+        Binarize::Otsu => {
+            name.push("otsu".to_string());
+            steps.push(Step::Otsu);
+        }
+        Binarize::Sauvola(k) => {
+            name.push(format!("sauvola{k}"));
+            steps.push(Step::Sauvola {
+                window: 25,
+                k,
+                r: 128.0,
+            });
+        }
+    }
+    if radius > 0 && !matches!(bin, Binarize::None) {
+        name.push(format!("open{radius}"));
+        steps.push(Step::Morphology {
+            operation: MorphOp::Open,
+            radius,
+        });
+    }
+    steps.push(Step::Pad {
+        pixels: 15,
+        value: 255,
+    });
+    Pipeline {
+        name: name.join("-"),
+        steps,
+    }
+}
+
+/// Fisher–Yates with a SplitMix64 stream seeded by `seed`.
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+// ── Saving ────────────────────────────────────────────────────────────────────
+
+/// Write `settings` for `region` into every layout of the project that has
+/// the region with the `tuned` settings: the shared config, inline entry
+/// configs and `config_path` files.  Layouts configured otherwise were not
+/// what the trials improved on and are left alone.
+fn store_settings(
+    project_path: &str,
+    mut project: Project,
+    region: &str,
+    tuned: &RegionSettings,
+    settings: &RegionSettings,
+) -> Result<(), String> {
+    let holds_tuned = |c: &RegionConfig| {
+        let present = c
+            .keyframes
+            .iter()
+            .any(|k| k.regions.iter().any(|r| r.name == region));
+        let current = c.region_settings.get(region).cloned().unwrap_or_default();
+        if present && !same_settings(&current, tuned) {
+            eprintln!("[tune] {region}: a layout with other settings was left unchanged");
+            return false;
+        }
+        present
+    };
+    let update = |c: &mut RegionConfig| {
+        if holds_tuned(c) {
+            c.region_settings
+                .insert(region.to_string(), settings.clone());
+        }
+    };
+
+    if let Some(c) = project.config.as_mut() {
+        update(c);
+    }
+    let mut written: Vec<String> = Vec::new();
+    for entry in &mut project.entries {
+        if let Some(c) = entry.config.as_mut() {
+            update(c);
+        } else if let Some(cp) = entry.config_path.as_deref() {
+            let path = resolve_path(Path::new(project_path), cp);
+            if written.contains(&path) || !Path::new(&path).exists() {
+                continue;
+            }
+            written.push(path.clone());
+            let mut config = load_config(path.clone())?;
+            if holds_tuned(&config) {
+                update(&mut config);
+                save_config(path, config)?;
+            }
+        }
+    }
+    save_project(project_path.to_string(), project)
+}
+
+/// Settings compare by their serialised form (as engine sets are keyed).
+fn same_settings(a: &RegionSettings, b: &RegionSettings) -> bool {
+    serde_json::to_string(a).ok() == serde_json::to_string(b).ok()
+}
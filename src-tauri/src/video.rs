@@ -70,115 +70,215 @@ pub fn get_video_info(path: String) -> Result<VideoInfo, String> {
     })
 }
 
-/// Decode the frame nearest to `timestamp` seconds and return
-/// `(rgb24_bytes, width, height)`.  The caller can use this for both
-/// interactive preview (→ JPEG) and bulk extraction (→ raw RGB crop).
-pub fn decode_frame_at(path: &str, timestamp: f64) -> Result<(Vec<u8>, u32, u32), String> {
-    ffmpeg::init().map_err(|e| e.to_string())?;
+/// A decoded RGB24 frame: `(bytes, width, height)`.
+pub type RgbFrame = (Vec<u8>, u32, u32);
 
-    let mut ictx = ffmpeg::format::input(&path).map_err(|e| format!("open '{path}': {e}"))?;
-
-    // Collect stream index + build decoder inside a block so that the
-    // shared borrow of `ictx` (held by `stream`) is released before we
-    // call `ictx.seek()` which needs a mutable borrow.
-    let (stream_idx, mut decoder) = {
-        let stream = ictx
-            .streams()
-            .best(Type::Video)
-            .ok_or_else(|| "no video stream".to_string())?;
-        let idx = stream.index();
-        let ctx = CodecCtx::from_parameters(stream.parameters())
-            .map_err(|e| format!("codec context: {e}"))?;
-        let dec = ctx
-            .decoder()
-            .video()
-            .map_err(|e| format!("video decoder: {e}"))?;
-        (idx, dec) // `stream` dropped here, releasing the shared borrow
-    };
+/// An opened video stream ready for decoding into RGB24.
+struct Decoding {
+    ictx: ffmpeg::format::context::Input,
+    stream_idx: usize,
+    decoder: ffmpeg::decoder::Video,
+    scaler: SwsCtx,
+    /// Stream time base, seconds per pts unit.
+    time_base: f64,
+    /// Stream start time in pts units (0 when unknown).
+    start: i64,
+}
 
-    let width = decoder.width();
-    let height = decoder.height();
-
-    // Seek to the requested time (AV_TIME_BASE = 1 000 000 µs / s).
-    // `..seek_ts` (RangeTo) passes max_ts = seek_ts directly to
-    // avformat_seek_file → (INT64_MIN, ts, ts), landing at the nearest
-    // keyframe ≤ timestamp.  playa-ffmpeg's Range trait does not implement
-    // RangeToInclusive, but RangeTo already gives the same result.
-    let seek_ts = (timestamp.max(0.0) * 1_000_000.0) as i64;
-    ictx.seek(seek_ts, ..seek_ts)
-        .map_err(|e| format!("seek to {timestamp:.3}s: {e}"))?;
-    decoder.flush(); // clear decoder buffers after seek
-
-    // Pixel-format converter: native format → RGB24
-    let mut scaler = SwsCtx::get(
-        decoder.format(),
-        width,
-        height,
-        Pixel::RGB24,
-        width,
-        height,
-        Flags::BILINEAR,
-    )
-    .map_err(|e| format!("scaler init: {e}"))?;
+impl Decoding {
+    fn open(path: &str) -> Result<Self, String> {
+        ffmpeg::init().map_err(|e| e.to_string())?;
 
-    let mut rgb_frame = VideoFrame::empty();
-    let mut found = false;
+        let ictx = ffmpeg::format::input(&path).map_err(|e| format!("open '{path}': {e}"))?;
 
-    'outer: for (stream, packet) in ictx.packets() {
-        if stream.index() != stream_idx {
-            continue;
-        }
-        if decoder.send_packet(&packet).is_err() {
-            continue;
-        }
-        let mut decoded = VideoFrame::empty();
-        while decoder.receive_frame(&mut decoded).is_ok() {
-            scaler
-                .run(&decoded, &mut rgb_frame)
-                .map_err(|e| format!("pixel convert: {e}"))?;
-            found = true;
-            break 'outer;
-        }
+        // Collect stream details + build decoder inside a block so that the
+        // shared borrow of `ictx` (held by `stream`) is released before it is
+        // moved into the result.
+        let (stream_idx, decoder, time_base, start) = {
+            let stream = ictx
+                .streams()
+                .best(Type::Video)
+                .ok_or_else(|| "no video stream".to_string())?;
+            let idx = stream.index();
+            let ctx = CodecCtx::from_parameters(stream.parameters())
+                .map_err(|e| format!("codec context: {e}"))?;
+            let dec = ctx
+                .decoder()
+                .video()
+                .map_err(|e| format!("video decoder: {e}"))?;
+            let tb = stream.time_base();
+            let time_base = if tb.1 != 0 {
+                tb.0 as f64 / tb.1 as f64
+            } else {
+                0.0
+            };
+            let start = Some(stream.start_time()).filter(|&s| s != i64::MIN);
+            (idx, dec, time_base, start.unwrap_or(0))
+        };
+
+        // Pixel-format converter: native format → RGB24
+        let scaler = SwsCtx::get(
+            decoder.format(),
+            decoder.width(),
+            decoder.height(),
+            Pixel::RGB24,
+            decoder.width(),
+            decoder.height(),
+            Flags::BILINEAR,
+        )
+        .map_err(|e| format!("scaler init: {e}"))?;
+
+        Ok(Decoding {
+            ictx,
+            stream_idx,
+            decoder,
+            scaler,
+            time_base,
+            start,
+        })
     }
 
-    if !found {
-        return Err(format!("no frame decoded at {timestamp:.3}s"));
+    /// Seek to the keyframe at or before `timestamp` seconds.
+    fn seek(&mut self, timestamp: f64) -> Result<(), String> {
+        // AV_TIME_BASE = 1 000 000 µs / s.  `..seek_ts` (RangeTo) passes
+        // max_ts = seek_ts directly to avformat_seek_file → (INT64_MIN, ts, ts),
+        // landing at the nearest keyframe ≤ timestamp.  playa-ffmpeg's Range
+        // trait does not implement RangeToInclusive, but RangeTo already gives
+        // the same result.
+        let seek_ts = (timestamp.max(0.0) * 1_000_000.0) as i64;
+        self.ictx
+            .seek(seek_ts, ..seek_ts)
+            .map_err(|e| format!("seek to {timestamp:.3}s: {e}"))?;
+        self.decoder.flush(); // clear decoder buffers after seek
+        Ok(())
     }
 
-    // Copy RGB data, stripping per-row padding if the stride > row width.
-    let stride = rgb_frame.stride(0);
-    let row_bytes = width as usize * 3;
-    let data = rgb_frame.data(0);
-
-    let rgb = if stride == row_bytes {
-        let expected = row_bytes * height as usize;
-        if data.len() < expected {
-            return Err(format!(
-                "frame buffer too small: {} bytes < {} expected ({}×{}×3)",
-                data.len(),
-                expected,
-                width,
-                height
-            ));
-        }
-        data[..expected].to_vec()
-    } else {
-        let mut flat = Vec::with_capacity(row_bytes * height as usize);
-        for row in 0..height as usize {
-            let start = row * stride;
-            let end = start + row_bytes;
-            if end > data.len() {
+    /// Stream time of a decoded frame in seconds, when it carries a timestamp.
+    fn seconds(&self, frame: &VideoFrame) -> Option<f64> {
+        let pts = frame.timestamp().or(frame.pts())?;
+        Some((pts - self.start) as f64 * self.time_base)
+    }
+
+    /// Convert a decoded frame to tightly packed RGB24.
+    fn rgb(&mut self, decoded: &VideoFrame) -> Result<RgbFrame, String> {
+        let (width, height) = (self.decoder.width(), self.decoder.height());
+        let mut rgb_frame = VideoFrame::empty();
+        self.scaler
+            .run(decoded, &mut rgb_frame)
+            .map_err(|e| format!("pixel convert: {e}"))?;
+
+        // Copy RGB data, stripping per-row padding if the stride > row width.
+        let stride = rgb_frame.stride(0);
+        let row_bytes = width as usize * 3;
+        let data = rgb_frame.data(0);
+
+        let rgb = if stride == row_bytes {
+            let expected = row_bytes * height as usize;
+            if data.len() < expected {
                 return Err(format!(
-                    "frame row {row} out of bounds (stride={stride}, data.len()={})",
-                    data.len()
+                    "frame buffer too small: {} bytes < {} expected ({}×{}×3)",
+                    data.len(),
+                    expected,
+                    width,
+                    height
                 ));
             }
-            flat.extend_from_slice(&data[start..end]);
+            data[..expected].to_vec()
+        } else {
+            let mut flat = Vec::with_capacity(row_bytes * height as usize);
+            for row in 0..height as usize {
+                let start = row * stride;
+                let end = start + row_bytes;
+                if end > data.len() {
+                    return Err(format!(
+                        "frame row {row} out of bounds (stride={stride}, data.len()={})",
+                        data.len()
+                    ));
+                }
+                flat.extend_from_slice(&data[start..end]);
+            }
+            flat
+        };
+        Ok((rgb, width, height))
+    }
+
+    /// Decode forward from the current position, handing every frame to
+    /// `visit` until it returns `false` or the stream ends.
+    fn decode_forward(
+        &mut self,
+        mut visit: impl FnMut(&mut Self, &VideoFrame) -> Result<bool, String>,
+    ) -> Result<(), String> {
+        let mut decoded = VideoFrame::empty();
+        let stream_idx = self.stream_idx;
+        // One packet at a time, so `self` is free for `visit` in between.
+        while let Some(packet) = self
+            .ictx
+            .packets()
+            .find(|(stream, _)| stream.index() == stream_idx)
+            .map(|(_, packet)| packet)
+        {
+            if self.decoder.send_packet(&packet).is_err() {
+                continue;
+            }
+            while self.decoder.receive_frame(&mut decoded).is_ok() {
+                if !visit(self, &decoded)? {
+                    return Ok(());
+                }
+            }
         }
-        flat
+        // Drain frames still buffered in the decoder.
+        let _ = self.decoder.send_eof();
+        while self.decoder.receive_frame(&mut decoded).is_ok() {
+            if !visit(self, &decoded)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decode the frame nearest to `timestamp` seconds and return
+/// `(rgb24_bytes, width, height)`.  The caller can use this for both
+/// interactive preview (→ JPEG) and bulk extraction (→ raw RGB crop).
+pub fn decode_frame_at(path: &str, timestamp: f64) -> Result<RgbFrame, String> {
+    let mut video = Decoding::open(path)?;
+    video.seek(timestamp)?;
+
+    let mut frame = None;
+    video.decode_forward(|video, decoded| {
+        frame = Some(video.rgb(decoded)?);
+        Ok(false)
+    })?;
+    frame.ok_or_else(|| format!("no frame decoded at {timestamp:.3}s"))
+}
+
+/// Decode the frames numbered `frames` (frame n shows at n / `fps` seconds)
+/// in one pass: seek once before the earliest, then decode forward and keep
+/// the wanted frames by their presentation time.  Frames that do not exist
+/// (past the end of the video) are missing from the result.
+pub fn decode_frames(
+    path: &str,
+    fps: f64,
+    frames: &[u64],
+) -> Result<std::collections::HashMap<u64, RgbFrame>, String> {
+    let mut out = std::collections::HashMap::new();
+    let (Some(&first), Some(&last)) = (frames.iter().min(), frames.iter().max()) else {
+        return Ok(out);
     };
+    let mut video = Decoding::open(path)?;
+    video.seek(first as f64 / fps)?;
 
-    Ok((rgb, width, height))
+    video.decode_forward(|video, decoded| {
+        let Some(t) = video.seconds(decoded) else {
+            return Ok(true);
+        };
+        let n = (t * fps).round();
+        if n >= 0.0 && frames.contains(&(n as u64)) && !out.contains_key(&(n as u64)) {
+            out.insert(n as u64, video.rgb(decoded)?);
+        }
+        Ok(n < last as f64)
+    })?;
+    Ok(out)
 }
 
 /// Extract the frame at `timestamp` and return a base64-encoded lossless PNG.
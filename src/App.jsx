@@ -67,6 +67,7 @@ function Sidebar({
   vpath, vinfo, onLoadVideo, videoError,
   names, onRenameRegion, onDeleteRegion,
   expectations, onSetExpectation,
+  onAcceptSuggestion,
   keyframes, ts, onSeekTo, onDeleteKf,
   onSaveConfig, onLoadConfig,
   isDirty,
@@ -75,6 +76,28 @@ function Sidebar({
   const [cfgMsg, setCfgMsg]           = useState('');
   const [expandedRegion, setExpanded] = useState(null);
   const [showKfHelp, setShowKfHelp]   = useState(false);
+  const [suggestions, setSuggestions] = useState([]);
+  const [suggesting, setSuggesting]   = useState(false);
+  const [suggestError, setSuggestError] = useState('');
+
+  async function suggestRegions() {
+    setSuggesting(true);
+    setSuggestError('');
+    try {
+      const found = await invoke('suggest_regions', { path: vpath, timestamp: ts });
+      setSuggestions(found);
+      if (!found.length) setSuggestError('No text found on this frame.');
+    } catch (e) {
+      setSuggestError('Error: ' + e);
+    } finally {
+      setSuggesting(false);
+    }
+  }
+
+  function acceptSuggestion(i) {
+    onAcceptSuggestion(suggestions[i]);
+    setSuggestions(s => s.filter((_, j) => j !== i));
+  }
 
   async function pickAndLoadVideo() {
     const path = await openDialog({
@@ -229,6 +252,23 @@ function Sidebar({
                           value={exp.max_deviation ?? ''} placeholder="unlimited"
                           onChange={e => set('max_deviation', e.target.value)} className="!py-0.5" />
                       </div>
+
+                      {/* Unit */}
+                      <div>
+                        <span className="text-xs font-medium text-gray-400 uppercase tracking-wider">Unit</span>
+                        <div className="grid grid-cols-2 gap-1 mt-1">
+                          <div>
+                            <Label>Quantity</Label>
+                            <Input type="text" value={exp.quantity ?? ''} placeholder="any"
+                              onChange={e => set('quantity', e.target.value)} className="!py-0.5" />
+                          </div>
+                          <div>
+                            <Label>Normalise to</Label>
+                            <Input type="text" value={exp.unit ?? ''} placeholder="as shown"
+                              onChange={e => set('unit', e.target.value)} className="!py-0.5" />
+                          </div>
+                        </div>
+                      </div>
                     </>)}
                   </div>
                 )}
@@ -236,6 +276,25 @@ function Sidebar({
             );
           })
         }
+
+        {/* Suggestions from text detection on the current frame */}
+        <Btn full disabled={!vinfo || suggesting} onClick={suggestRegions}>
+          {suggesting ? 'Detecting text…' : 'Suggest regions'}
+        </Btn>
+        {suggestError && <span className="text-xs text-red-500">{suggestError}</span>}
+        {suggestions.map((s, i) => (
+          <div
+            key={`${s.region.name}-${i}`}
+            className="flex items-center gap-1 rounded border border-blue-100 bg-blue-50 px-2 py-1 text-xs"
+          >
+            <span className="flex-1 min-w-0 truncate" title={`${s.text} (${Math.round(s.confidence * 100)}%)`}>
+              <span className="font-medium text-gray-700">{s.region.name}</span>
+              <span className="ml-1 font-mono text-gray-500">{s.text}</span>
+            </span>
+            <Btn variant="ghost" title="Add region" onClick={() => acceptSuggestion(i)}>✓</Btn>
+            <Btn variant="ghost" title="Dismiss" onClick={() => setSuggestions(ss => ss.filter((_, j) => j !== i))}>✕</Btn>
+          </div>
+        ))}
       </Card>
 
       {/* Keyframes */}
@@ -363,17 +422,20 @@ function SortTh({ col, label, sortCol, sortDir, onSort }) {
   );
 }
 
-function ExtractTab({ vpath, vinfo, keyframes, expectations }) {
+function ExtractTab({ vpath, vinfo, keyframes, expectations, configExtras }) {
   const [fpsSample,     setFpsSample]     = useState(30);
   const [lang,          setLang]          = useState('en,de');
   const [preprocess,    setPreprocess]    = useState(true);
   const [oarThreshold,  setOarThreshold]  = useState(90);
+  const [selection,     setSelection]     = useState('best');
+  const [exportSymbols, setExportSymbols] = useState(false);
   const [showAdvanced,  setShowAdvanced]  = useState(false);
   const [running,       setRunning]       = useState(false);
   const [results,       setResults]       = useState(null);
   const [csvData,       setCsvData]       = useState(null);
   const [progress,      setProgress]      = useState(null);
   const [extractError,  setExtractError]  = useState('');
+  const [warnings,      setWarnings]      = useState([]);
   const [exportError,   setExportError]   = useState('');
   const [sortCol,       setSortCol]       = useState('ts');
   const [sortDir,       setSortDir]       = useState('asc');
@@ -409,6 +471,7 @@ function ExtractTab({ vpath, vinfo, keyframes, expectations }) {
 
   async function startExtract() {
     setExtractError('');
+    setWarnings([]);
     if (!vpath) {
       setExtractError('Load a video first.');
       return;
@@ -437,7 +500,7 @@ function ExtractTab({ vpath, vinfo, keyframes, expectations }) {
       setLastPreviews(prev => {
         const next = { ...prev };
         p.regions.forEach(r => {
-          next[r.region_name] = { preview: r.ocr_preview, value: r.value, confidence: r.confidence, source: r.source };
+          next[r.region_name] = { preview: r.ocr_preview, value: r.value, confidence: r.confidence, source: r.source, symbols: r.symbols };
         });
         return next;
       });
@@ -457,15 +520,18 @@ function ExtractTab({ vpath, vinfo, keyframes, expectations }) {
       const res = await invoke('extract', {
         params: {
           video_path: vpath,
-          config: { video_path: vpath, keyframes, expectations: buildBackendExpectations(expectations) },
+          config: { ...configExtras, video_path: vpath, keyframes, expectations: buildBackendExpectations(expectations) },
           fps_sample: fpsSample,
           preprocess,
           languages: lang.split(',').map(s => s.trim()).filter(Boolean),
           oar_confidence_threshold: oarThreshold / 100,
+          selection,
+          export_symbols: exportSymbols,
         },
       });
       setResults(res.measurements);
       setCsvData(res.csv);
+      setWarnings(res.warnings || []);
       setProgress(null);
     } catch (e) {
       setExtractError('Extraction error: ' + e);
@@ -559,6 +625,18 @@ function ExtractTab({ vpath, vinfo, keyframes, expectations }) {
             <span className="text-xs text-gray-400 mt-0.5 block">
               Primary OCR result is used directly when confidence ≥ this value; lower values force more cross-checking.
             </span>
+            <div className="mt-3">
+              <Label>Result selection</Label>
+              <Select value={selection} onChange={e => setSelection(e.target.value)}>
+                <option value="best">Best single engine</option>
+                <option value="rover">Character voting across engines (ROVER)</option>
+              </Select>
+            </div>
+            <label className="flex items-center gap-2 text-sm text-gray-600 cursor-pointer mt-3">
+              <input type="checkbox" checked={exportSymbols} onChange={e => setExportSymbols(e.target.checked)}
+                className="accent-green-600" />
+              Export per-character confidences
+            </label>
           </div>
         )}
 
@@ -583,6 +661,11 @@ function ExtractTab({ vpath, vinfo, keyframes, expectations }) {
             {extractError}
           </div>
         )}
+        {warnings.map((w, i) => (
+          <div key={i} className="rounded border border-amber-200 bg-amber-50 px-2 py-1.5 text-xs text-amber-800">
+            {w}
+          </div>
+        ))}
         <div className="flex gap-2">
           <Btn variant="primary" full onClick={startExtract} disabled={running}>
             {running ? '⏳ Extracting…' : 'Start extraction'}
@@ -611,7 +694,7 @@ function ExtractTab({ vpath, vinfo, keyframes, expectations }) {
             <div className="mt-2 flex flex-col gap-1.5">
               <span className="text-xs text-gray-400 uppercase tracking-wider">Last OCR input per region</span>
               <div className="flex flex-wrap gap-3">
-                {Object.entries(lastPreviews).map(([name, { preview, value, confidence, source }]) => (
+                {Object.entries(lastPreviews).map(([name, { preview, value, confidence, source, symbols }]) => (
                   <div key={name} className="flex flex-col gap-1 min-w-0">
                     <div className="flex items-baseline gap-1.5 flex-wrap">
                       <span className="text-xs font-semibold text-gray-500 truncate">{name}</span>
@@ -630,6 +713,15 @@ function ExtractTab({ vpath, vinfo, keyframes, expectations }) {
                         </span>
                       )}
                     </div>
+                    {symbols?.length > 0 && (
+                      <div className="flex gap-px text-xs font-mono" aria-label={`Per-character confidence — ${name}`}>
+                        {symbols.map((s, i) => (
+                          <span key={i} title={`${Math.round(s.confidence * 100)}%`} style={{ color: confBar(s.confidence) }}>
+                            {s.text}
+                          </span>
+                        ))}
+                      </div>
+                    )}
                     <img
                       src={`data:image/png;base64,${preview}`}
                       alt={`OCR input — ${name}`}
@@ -729,6 +821,7 @@ function ExtractTab({ vpath, vinfo, keyframes, expectations }) {
 function buildBackendExpectations(exps) {
   const parseF = v => (v !== '' && v != null && !isNaN(+v)) ? +v : null;
   const parseI = v => (v !== '' && v != null && !isNaN(parseInt(v, 10))) ? parseInt(v, 10) : null;
+  const parseS = v => (v != null && v.trim() !== '') ? v.trim() : null;
   const out = {};
   for (const [name, exp] of Object.entries(exps)) {
     if (!exp?.numeric) continue;
@@ -739,6 +832,11 @@ function buildBackendExpectations(exps) {
       decimal_places: parseI(exp.decimal_places),
       total_digits:   parseI(exp.total_digits),
       max_deviation:  parseF(exp.max_deviation),
+      quantity:       parseS(exp.quantity),
+      unit:           parseS(exp.unit),
+      confusions:     exp.confusions ?? null,
+      detect_punctuation: exp.detect_punctuation ?? null,
+      exponent:       exp.exponent ?? false,
     };
   }
   return out;
@@ -755,6 +853,11 @@ function parseBackendExpectations(backendExps) {
       decimal_places: exp.decimal_places != null ? String(exp.decimal_places) : '',
       total_digits:   exp.total_digits   != null ? String(exp.total_digits)   : '',
       max_deviation:  exp.max_deviation  != null ? String(exp.max_deviation)  : '',
+      quantity:       exp.quantity ?? '',
+      unit:           exp.unit     ?? '',
+      confusions:     exp.confusions ?? null,
+      detect_punctuation: exp.detect_punctuation ?? null,
+      exponent:       exp.exponent ?? false,
     }])
   );
 }
@@ -767,6 +870,8 @@ export default function App() {
   const [names,        setNames]        = useState([]);
   const [keyframes,    setKeyframes]    = useState([]);
   const [expectations, setExpectations] = useState({});
+  // Config fields not edited in the UI (e.g. derived channels) — kept so save round-trips them.
+  const [configExtras, setConfigExtras] = useState({});
   const [ts,           setTs]           = useState(0);
   const [activeTab,    setActiveTab]    = useState('configure');
   const [videoError,   setVideoError]   = useState('');
@@ -809,7 +914,17 @@ export default function App() {
 
   // ── Region events ──────────────────────────────────────────────────────────
 
+  // Keyframes stay in the layout's reference resolution; the canvas maps them
+  // onto this video.  A layout without one takes this video's on first edit.
+  const markDrawnOnVideo = useCallback(() => {
+    if (!vinfo) return;
+    setConfigExtras(x => x.reference_resolution
+      ? x
+      : { ...x, reference_resolution: { width: vinfo.width, height: vinfo.height } });
+  }, [vinfo]);
+
   const handleRegionDrawn = useCallback((videoRect, name) => {
+    markDrawnOnVideo();
     const tRounded = parseFloat(ts.toFixed(3));
     const newNames = [...names, name];
     setNames(newNames);
@@ -826,9 +941,10 @@ export default function App() {
       return [...updatedKfs, { timestamp: tRounded, regions }]
         .sort((a, b) => a.timestamp - b.timestamp);
     });
-  }, [ts, names]);
+  }, [ts, names, markDrawnOnVideo]);
 
   const handleRegionMoved = useCallback((name, videoRect) => {
+    markDrawnOnVideo();
     const tRounded = parseFloat(ts.toFixed(3));
     const willCreate = !keyframes.some(kf => kf.timestamp === tRounded);
     setKeyframes(kfs => {
@@ -841,7 +957,7 @@ export default function App() {
         .sort((a, b) => a.timestamp - b.timestamp);
     });
     if (willCreate) showToast(`Keyframe saved at t=${tRounded.toFixed(2)}s`);
-  }, [ts, names, keyframes, showToast]);
+  }, [ts, names, keyframes, showToast, markDrawnOnVideo]);
 
   const handleRegionDeleted = useCallback((name) => {
     setNames(ns => ns.filter(n => n !== name));
@@ -865,6 +981,22 @@ export default function App() {
     });
   }
 
+  function acceptSuggestion({ region, expectation }) {
+    let name = region.name;
+    for (let n = 2; names.includes(name); n++) name = `${region.name}_${n}`;
+    // Suggestions come in this video's pixels.
+    const ref = configExtras.reference_resolution;
+    const fx = ref && vinfo ? ref.width / vinfo.width : 1;
+    const fy = ref && vinfo ? ref.height / vinfo.height : 1;
+    handleRegionDrawn({
+      x: Math.round(region.x * fx), y: Math.round(region.y * fy),
+      width: Math.round(region.width * fx), height: Math.round(region.height * fy),
+    }, name);
+    if (expectation) {
+      setExpectations(prev => ({ ...prev, ...parseBackendExpectations({ [name]: expectation }) }));
+    }
+  }
+
   function deleteKf(timestamp) {
     setKeyframes(kfs => kfs.filter(kf => kf.timestamp !== timestamp));
   }
@@ -873,8 +1005,11 @@ export default function App() {
     await invoke('save_config', {
       path,
       config: {
+        ...configExtras,
         video_path: vpath,
         keyframes,
+        reference_resolution: configExtras.reference_resolution
+          ?? (vinfo ? { width: vinfo.width, height: vinfo.height } : null),
         expectations: buildBackendExpectations(expectations),
       },
     });
@@ -883,6 +1018,7 @@ export default function App() {
 
   async function loadConfig(path) {
     const cfg = await invoke('load_config', { path });
+    const { video_path: _vp, keyframes: _kfs, expectations: _exps, ...extras } = cfg;
     const kfs = cfg.keyframes || [];
     const seen = new Set(); const ns = [];
     kfs.forEach(kf => kf.regions.forEach(r => {
@@ -893,6 +1029,7 @@ export default function App() {
     setNames(ns);
     if (cfg.video_path) setVpath(cfg.video_path);
     setExpectations(exps);
+    setConfigExtras(extras);
     setSavedSnapshot(JSON.stringify({ names: ns, keyframes: kfs, expectations: exps }));
   }
 
@@ -914,6 +1051,7 @@ export default function App() {
           onDeleteRegion={handleRegionDeleted}
           expectations={expectations}
           onSetExpectation={setExpectation}
+          onAcceptSuggestion={acceptSuggestion}
           keyframes={keyframes}
           ts={ts}
           onSeekTo={setTs}
@@ -966,6 +1104,7 @@ export default function App() {
               ref={canvasRef}
               names={names}
               keyframes={keyframes}
+              reference={configExtras.reference_resolution}
               ts={ts}
               vpath={vpath}
               vinfo={vinfo}
@@ -982,7 +1121,7 @@ export default function App() {
             className="flex-1 overflow-auto"
             style={{ display: activeTab === 'extract' ? 'flex' : 'none', flexDirection: 'column' }}
           >
-            <ExtractTab vpath={vpath} vinfo={vinfo} keyframes={keyframes} expectations={expectations} />
+            <ExtractTab vpath={vpath} vinfo={vinfo} keyframes={keyframes} expectations={expectations} configExtras={configExtras} />
           </div>
         </main>
       </div>
@@ -6,7 +6,7 @@ import {
 } from '../helpers.js';
 
 const CanvasPanel = forwardRef(function CanvasPanel(
-  { names, keyframes, ts, vpath, vinfo, onRegionDrawn, onRegionMoved, onRegionDeleted },
+  { names, keyframes, reference, ts, vpath, vinfo, onRegionDrawn, onRegionMoved, onRegionDeleted },
   ref
 ) {
   const cvRef        = useRef(null);
@@ -30,7 +30,11 @@ const CanvasPanel = forwardRef(function CanvasPanel(
   // canvas fills all available vertical space while keeping the video aspect ratio.
   const [cw, setCw] = useState(960);
   const [ch, setCh] = useState(540);
-  const scale = vinfo && cw > 0 ? cw / vinfo.width : 1;
+  // Keyframe coordinates are in the layout's reference resolution, which may
+  // differ from this video's; map them separately along each axis.
+  const base = vinfo && (reference ?? vinfo);
+  const scaleX = base && cw > 0 ? cw / base.width : 1;
+  const scaleY = base && ch > 0 ? ch / base.height : 1;
 
   // Measure the canvas container and update cw/ch to fit it.
   const sizeCanvas = useCallback(() => {
@@ -85,8 +89,8 @@ const CanvasPanel = forwardRef(function CanvasPanel(
       .filter(n => n in pos)
       .map(n => ({
         name: n,
-        x: pos[n].x * scale, y: pos[n].y * scale,
-        w: pos[n].width * scale, h: pos[n].height * scale,
+        x: pos[n].x * scaleX, y: pos[n].y * scaleY,
+        w: pos[n].width * scaleX, h: pos[n].height * scaleY,
       }));
 
     ctx.clearRect(0, 0, cv.width, cv.height);
@@ -152,7 +156,7 @@ const CanvasPanel = forwardRef(function CanvasPanel(
     }
 
     ctx.restore();
-  }, [keyframes, ts, names, scale]);
+  }, [keyframes, ts, names, scaleX, scaleY]);
 
   // Keep a ref to the latest draw so imperative callers (wheel, zoom buttons) always
   // get the freshest version without needing it in their deps arrays.
@@ -357,8 +361,8 @@ const CanvasPanel = forwardRef(function CanvasPanel(
       const n = norm(d.drawRect);
       if (n.w > 8 && n.h > 8) {
         onRegionDrawn(
-          { x: Math.round(n.x / scale), y: Math.round(n.y / scale),
-            width: Math.round(n.w / scale), height: Math.round(n.h / scale) },
+          { x: Math.round(n.x / scaleX), y: Math.round(n.y / scaleY),
+            width: Math.round(n.w / scaleX), height: Math.round(n.h / scaleY) },
           `region_${names.length + 1}`
         );
       }
@@ -366,8 +370,8 @@ const CanvasPanel = forwardRef(function CanvasPanel(
     } else if ((d.type === 'move' || d.type === 'resize') && d.currentRect) {
       const n = norm(d.currentRect);
       onRegionMoved(d.name, {
-        x: Math.round(n.x / scale), y: Math.round(n.y / scale),
-        width: Math.round(n.w / scale), height: Math.round(n.h / scale),
+        x: Math.round(n.x / scaleX), y: Math.round(n.y / scaleY),
+        width: Math.round(n.w / scaleX), height: Math.round(n.h / scaleY),
       });
     }
     const prev = d.name;